@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::serialization::{DbContents, DbDictMap, DbExpiryMap, DbListMap, DbMap, Serializer};
+
+// Every record of the append log holds the latest state of the keys changed by a single mutation, so
+// replaying a record more than once, or over a snapshot that already has the change, gives the same DB.
+// A record is laid out as:
+//
+//   [removed keys length: u64 LE][contents length: u64 LE][removed keys][contents]
+//
+// `removed keys` is the list of keys that no longer exist, serialized as a single value, and `contents` is
+// a DB file holding just the changed keys that still exist, along with their expiry times, both written by
+// the serializer of the DB.
+const RECORD_HEADER_LEN: usize = 16;
+
+/// Encode a record with the current state of `keys`.
+pub(crate) fn encode_record(
+    serializer: &Serializer,
+    keys: &[&str],
+    map: &DbMap,
+    list_map: &DbListMap,
+    dict_map: &DbDictMap,
+    expiry_map: &DbExpiryMap,
+) -> Result<Vec<u8>, String> {
+    let mut removed = Vec::new();
+    let mut changed_map = HashMap::new();
+    let mut changed_list_map = HashMap::new();
+    let mut changed_dict_map = HashMap::new();
+    let mut changed_expiry_map = HashMap::new();
+    for key in keys {
+        if let Some(expires_at) = expiry_map.get(*key) {
+            changed_expiry_map.insert(key.to_string(), *expires_at);
+        }
+        if let Some(value) = map.get(*key) {
+            changed_map.insert(key.to_string(), value.clone());
+        } else if let Some(list) = list_map.get(*key) {
+            changed_list_map.insert(key.to_string(), list.clone());
+        } else if let Some(dict) = dict_map.get(*key) {
+            changed_dict_map.insert(key.to_string(), dict.clone());
+        } else {
+            removed.push(*key);
+        }
+    }
+
+    let ser_removed = serializer.serialize_data(&removed)?;
+    let ser_contents = serializer.serialize_db(
+        &changed_map,
+        &changed_list_map,
+        &changed_dict_map,
+        &changed_expiry_map,
+    )?;
+
+    let mut record = Vec::with_capacity(RECORD_HEADER_LEN + ser_removed.len() + ser_contents.len());
+    record.extend_from_slice(&(ser_removed.len() as u64).to_le_bytes());
+    record.extend_from_slice(&(ser_contents.len() as u64).to_le_bytes());
+    record.extend_from_slice(&ser_removed);
+    record.extend_from_slice(&ser_contents);
+    Ok(record)
+}
+
+/// Replay the records of an append log over the contents of a DB snapshot.
+///
+/// Returns the length of the log up to the end of the last complete record. A final record that is cut
+/// short or can't be read, which is what a crash in the middle of an append leaves behind, is ignored.
+pub(crate) fn replay(
+    serializer: &Serializer,
+    log: &[u8],
+    db: &mut DbContents,
+) -> Result<usize, String> {
+    let mut pos = 0;
+    while log.len() - pos >= RECORD_HEADER_LEN {
+        let record = &log[pos..];
+        let removed_len = u64::from_le_bytes(record[0..8].try_into().unwrap());
+        let contents_len = u64::from_le_bytes(record[8..16].try_into().unwrap());
+        let record_len = match (RECORD_HEADER_LEN as u64)
+            .checked_add(removed_len)
+            .and_then(|len| len.checked_add(contents_len))
+        {
+            Some(len) if len <= record.len() as u64 => len as usize,
+            _ => break,
+        };
+
+        let contents_start = RECORD_HEADER_LEN + removed_len as usize;
+        let removed =
+            serializer.deserialize_data::<Vec<String>>(&record[RECORD_HEADER_LEN..contents_start]);
+        let contents = serializer.deserialize_db(&record[contents_start..record_len]);
+        let is_last = pos + record_len == log.len();
+        match (removed, contents) {
+            (Some(removed), Ok(contents)) => apply_record(db, removed, contents),
+            _ if is_last => break,
+            (None, _) => {
+                return Err(format!(
+                    "invalid removed keys in the append log record at byte {}",
+                    pos
+                ))
+            }
+            (_, Err(err_str)) => return Err(err_str),
+        }
+        pos += record_len;
+    }
+    Ok(pos)
+}
+
+fn apply_record(db: &mut DbContents, removed: Vec<String>, contents: DbContents) {
+    let changed = removed
+        .iter()
+        .chain(contents.map.keys())
+        .chain(contents.list_map.keys())
+        .chain(contents.dict_map.keys());
+    for key in changed {
+        db.map.remove(key);
+        db.list_map.remove(key);
+        db.dict_map.remove(key);
+        db.expiry_map.remove(key);
+    }
+    db.map.extend(contents.map);
+    db.list_map.extend(contents.list_map);
+    db.dict_map.extend(contents.dict_map);
+    db.expiry_map.extend(contents.expiry_map);
+}
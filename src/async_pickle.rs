@@ -0,0 +1,265 @@
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, PoisonError};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::runtime::Handle;
+use tokio::sync::{Mutex, Notify, OwnedMutexGuard};
+use tokio::task::{self, JoinHandle};
+
+use crate::error::{Error, Result};
+use crate::rustypickle::{DumpPolicy, Pickle};
+use crate::serialization::SerializationMethod;
+
+/// A `Pickle` for tokio based code, available with the `tokio` feature.
+///
+/// Reading and writing the DB file happens on the blocking thread pool of the runtime instead of on the
+/// executor. Under [DumpPolicy::Auto] a change doesn't wait for the DB to be dumped: a background task
+/// dumps it instead, and all the changes made while that task waits to run or writes the file go into a
+/// single dump. [AsyncPickle::dump()](#method.dump) and [AsyncPickle::close()](#method.close) wait for the
+/// changes to be in the DB file.
+///
+/// When a background dump fails its changes stay in the DB and are dumped again along with the next
+/// change. The error is returned by the next call to [AsyncPickle::set()](#method.set),
+/// [AsyncPickle::remove()](#method.remove) or [AsyncPickle::dump()](#method.dump), which then doesn't
+/// make its own change or dump.
+///
+/// When the `AsyncPickle` is dropped the changes left are dumped in the background, as long as the runtime
+/// is still running.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rusty_pickle::{AsyncPickle, DumpPolicy, SerializationMethod};
+///
+/// # async fn example() -> rusty_pickle::error::Result<()> {
+/// let db = AsyncPickle::load("example.db", DumpPolicy::Auto, SerializationMethod::Json).await?;
+/// db.set("key1", &100).await?;
+/// db.close().await
+/// # }
+/// ```
+pub struct AsyncPickle {
+    db: Arc<Mutex<Pickle>>,
+    blocking_writes: bool,
+    flush: Arc<Flush>,
+    flusher: Option<JoinHandle<Result<()>>>,
+}
+
+struct Flush {
+    changed: Notify,
+    closed: AtomicBool,
+    // the error of the last background dump, until it's returned
+    error: StdMutex<Option<Error>>,
+}
+
+impl Flush {
+    fn take_error(&self) -> Result<()> {
+        match self
+            .error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AsyncPickle {
+    /// Constructs a new `AsyncPickle` instance, see [Pickle::new()](struct.Pickle.html#method.new). This
+    /// must be called from within a tokio runtime.
+    pub fn new<P: AsRef<Path>>(
+        db_path: P,
+        dump_policy: DumpPolicy,
+        serialization_method: SerializationMethod,
+    ) -> AsyncPickle {
+        AsyncPickle::from_pickle(Pickle::new(db_path, dump_policy, serialization_method))
+    }
+
+    /// Load a DB from a file, see [Pickle::load()](struct.Pickle.html#method.load).
+    pub async fn load<P: AsRef<Path>>(
+        db_path: P,
+        dump_policy: DumpPolicy,
+        serialization_method: SerializationMethod,
+    ) -> Result<AsyncPickle> {
+        let db_path = PathBuf::from(db_path.as_ref());
+        let db =
+            run_blocking(move || Pickle::load(db_path, dump_policy, serialization_method)).await?;
+        Ok(AsyncPickle::from_pickle(db))
+    }
+
+    /// Use a `Pickle` that was already constructed or loaded, for instance with a custom serializer or a
+    /// lock. This must be called from within a tokio runtime.
+    pub fn from_pickle(mut db: Pickle) -> AsyncPickle {
+        // under DumpPolicy::Auto the Pickle itself is only dumped on request, by the flusher task
+        let auto_dump = matches!(db.dump_policy(), DumpPolicy::Auto);
+        if auto_dump {
+            db.set_dump_policy(DumpPolicy::UponRequest);
+        }
+        let blocking_writes = matches!(
+            db.dump_policy(),
+            DumpPolicy::Periodic(_) | DumpPolicy::AppendLog
+        );
+        let db = Arc::new(Mutex::new(db));
+        let flush = Arc::new(Flush {
+            changed: Notify::new(),
+            closed: AtomicBool::new(false),
+            error: StdMutex::new(None),
+        });
+        let flusher = match auto_dump {
+            true => Some(task::spawn(flush_on_change(
+                Arc::clone(&db),
+                Arc::clone(&flush),
+            ))),
+            false => None,
+        };
+
+        AsyncPickle {
+            db,
+            blocking_writes,
+            flush,
+            flusher,
+        }
+    }
+
+    /// Get the value of a key, see [Pickle::get()](struct.Pickle.html#method.get).
+    pub async fn get<V>(&self, key: &str) -> Option<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.db.lock().await.get(key)
+    }
+
+    /// Check if a key exists, see [Pickle::exists()](struct.Pickle.html#method.exists).
+    pub async fn exists(&self, key: &str) -> bool {
+        self.db.lock().await.exists(key)
+    }
+
+    /// Check if the DB has changes that aren't in the DB file yet, see
+    /// [Pickle::is_dirty()](struct.Pickle.html#method.is_dirty). This waits for a dump that is being
+    /// written to finish.
+    pub async fn is_dirty(&self) -> bool {
+        self.db.lock().await.is_dirty()
+    }
+
+    /// Set a key and its respective value, see [Pickle::set()](struct.Pickle.html#method.set).
+    pub async fn set<V>(&self, key: &str, value: &V) -> Result<()>
+    where
+        V: Serialize,
+    {
+        self.flush.take_error()?;
+        let db = Arc::clone(&self.db).lock_owned().await;
+        let ser_data = db.serialize_value(value)?;
+        let key = String::from(key);
+        self.write(db, move |db| db.set_raw(&key, ser_data)).await
+    }
+
+    /// Remove a key, see [Pickle::remove()](struct.Pickle.html#method.remove).
+    pub async fn remove(&self, key: &str) -> Result<bool> {
+        self.flush.take_error()?;
+        let db = Arc::clone(&self.db).lock_owned().await;
+        let key = String::from(key);
+        self.write(db, move |db| db.remove(&key)).await
+    }
+
+    /// Dump the DB into the file, see [Pickle::dump()](struct.Pickle.html#method.dump).
+    pub async fn dump(&self) -> Result<()> {
+        self.flush.take_error()?;
+        let mut db = Arc::clone(&self.db).lock_owned().await;
+        run_blocking(move || db.dump()).await
+    }
+
+    /// Dump any changes that are left and stop the background task.
+    ///
+    /// This is what dropping the `AsyncPickle` does as well, except that it waits for the last dump and
+    /// returns its error instead of losing it.
+    pub async fn close(mut self) -> Result<()> {
+        match self.flusher.take() {
+            Some(flusher) => {
+                self.stop_flusher();
+                match flusher.await {
+                    Ok(result) => result,
+                    Err(err) => panic::resume_unwind(err.into_panic()),
+                }
+            }
+            None => {
+                let mut db = Arc::clone(&self.db).lock_owned().await;
+                run_blocking(move || db.dump_pending()).await
+            }
+        }
+    }
+
+    // Apply a change to the DB, off the executor if the dump policy may write files for it.
+    async fn write<T, F>(&self, mut db: OwnedMutexGuard<Pickle>, change: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Pickle) -> Result<T> + Send + 'static,
+    {
+        if self.blocking_writes {
+            return run_blocking(move || change(&mut db)).await;
+        }
+
+        let result = change(&mut db);
+        drop(db);
+        if self.flusher.is_some() {
+            self.flush.changed.notify_one();
+        }
+        result
+    }
+
+    fn stop_flusher(&self) {
+        self.flush.closed.store(true, Ordering::SeqCst);
+        self.flush.changed.notify_one();
+    }
+}
+
+impl Drop for AsyncPickle {
+    fn drop(&mut self) {
+        if self.flusher.is_some() {
+            self.stop_flusher();
+            return;
+        }
+
+        // without a runtime the Pickle dumps its changes itself when it's dropped
+        if let Ok(runtime) = Handle::try_current() {
+            let db = Arc::clone(&self.db);
+            runtime.spawn_blocking(move || {
+                let _ = db.blocking_lock().dump_pending();
+            });
+        }
+    }
+}
+
+async fn run_blocking<T, F>(work: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    match task::spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(err) => panic::resume_unwind(err.into_panic()),
+    }
+}
+
+// A failed dump leaves the changes pending, so it's retried after the next change, and its error is kept
+// for the AsyncPickle to return. The result of the last dump, made once the AsyncPickle is closed, is
+// returned.
+async fn flush_on_change(db: Arc<Mutex<Pickle>>, flush: Arc<Flush>) -> Result<()> {
+    loop {
+        flush.changed.notified().await;
+        let closed = flush.closed.load(Ordering::SeqCst);
+
+        // let the rest of a burst of changes in before dumping
+        task::yield_now().await;
+        let mut guard = Arc::clone(&db).lock_owned().await;
+        let result = run_blocking(move || guard.dump_pending()).await;
+        if closed {
+            return result;
+        }
+        // a dump that works saves the changes of the one that failed before it
+        *flush.error.lock().unwrap_or_else(PoisonError::into_inner) = result.err();
+    }
+}
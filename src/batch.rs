@@ -0,0 +1,62 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::error::Result;
+use crate::rustypickle::Pickle;
+
+/// A batch of changes to a `Pickle` that are dumped together, see
+/// [Pickle::batch()](struct.Pickle.html#method.batch).
+///
+/// The batch derefs to the `Pickle`, so it's changed with the usual methods.
+pub struct PickleBatch<'a> {
+    db: &'a mut Pickle,
+    // a batch inside a transaction or another batch is dumped along with it
+    nested: bool,
+    committed: bool,
+}
+
+impl<'a> PickleBatch<'a> {
+    pub(crate) fn new(db: &'a mut Pickle) -> PickleBatch<'a> {
+        let nested = !db.suspend_dumps();
+        PickleBatch {
+            db,
+            nested,
+            committed: false,
+        }
+    }
+
+    /// Dump the changes of the batch. This is what dropping the batch does as well, except that the error
+    /// of the dump is returned instead of being lost.
+    pub fn commit(mut self) -> Result<()> {
+        self.committed = true;
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match self.nested {
+            true => Ok(()),
+            false => self.db.resume_dumps(),
+        }
+    }
+}
+
+impl Deref for PickleBatch<'_> {
+    type Target = Pickle;
+
+    fn deref(&self) -> &Pickle {
+        self.db
+    }
+}
+
+impl DerefMut for PickleBatch<'_> {
+    fn deref_mut(&mut self) -> &mut Pickle {
+        self.db
+    }
+}
+
+impl Drop for PickleBatch<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = self.finish();
+        }
+    }
+}
@@ -8,6 +8,21 @@ pub enum ErrorType {
     Io,
     /// An error specific to attempting to serialize or deserialize the data.
     Serialization,
+    /// The requested list or dict, or a key inside a dict, does not exist in the DB.
+    NotFound,
+    /// A list position is out of the bounds of the list.
+    OutOfRange,
+    /// The DB file is locked by another `Pickle`, in this process or in another one, or the DB was opened
+    /// with a shared lock and can't be written.
+    Locked,
+    /// The value under a key isn't of the kind an operation works on, e.g. incrementing a value that isn't
+    /// a number.
+    WrongType,
+    /// The result of an operation doesn't fit its type, e.g. incrementing an integer past `i64::MAX`.
+    Overflow,
+    /// The key a value, list or dict is written to already exists, e.g. when renaming a key without
+    /// overwriting.
+    KeyExists,
 }
 
 pub struct Error {
@@ -26,6 +41,12 @@ impl Error {
         match self.err_code {
             ErrorCode::Io(_) => ErrorType::Io,
             ErrorCode::Serialization(_) => ErrorType::Serialization,
+            ErrorCode::NotFound(_) => ErrorType::NotFound,
+            ErrorCode::OutOfRange(_) => ErrorType::OutOfRange,
+            ErrorCode::Locked(_) => ErrorType::Locked,
+            ErrorCode::WrongType(_) => ErrorType::WrongType,
+            ErrorCode::Overflow(_) => ErrorType::Overflow,
+            ErrorCode::KeyExists(_) => ErrorType::KeyExists,
         }
     }
 }
@@ -35,6 +56,12 @@ impl fmt::Display for Error {
         match self.err_code {
             ErrorCode::Io(ref err) => fmt::Display::fmt(err, f),
             ErrorCode::Serialization(ref err_str) => f.write_str(err_str),
+            ErrorCode::NotFound(ref err_str) => f.write_str(err_str),
+            ErrorCode::OutOfRange(ref err_str) => f.write_str(err_str),
+            ErrorCode::Locked(ref err_str) => f.write_str(err_str),
+            ErrorCode::WrongType(ref err_str) => f.write_str(err_str),
+            ErrorCode::Overflow(ref err_str) => f.write_str(err_str),
+            ErrorCode::KeyExists(ref err_str) => f.write_str(err_str),
         }
     }
 }
@@ -46,6 +73,12 @@ impl fmt::Debug for Error {
             match self.err_code {
                 ErrorCode::Io(ref err) => err.to_string(),
                 ErrorCode::Serialization(ref err_str) => err_str.to_string(),
+                ErrorCode::NotFound(ref err_str) => err_str.to_string(),
+                ErrorCode::OutOfRange(ref err_str) => err_str.to_string(),
+                ErrorCode::Locked(ref err_str) => err_str.to_string(),
+                ErrorCode::WrongType(ref err_str) => err_str.to_string(),
+                ErrorCode::Overflow(ref err_str) => err_str.to_string(),
+                ErrorCode::KeyExists(ref err_str) => err_str.to_string(),
             }
         ))
     }
@@ -56,4 +89,10 @@ impl std::error::Error for Error {}
 pub(crate) enum ErrorCode {
     Io(io::Error),
     Serialization(String),
+    NotFound(String),
+    OutOfRange(String),
+    Locked(String),
+    WrongType(String),
+    Overflow(String),
+    KeyExists(String),
 }
@@ -1,3 +1,4 @@
+use crate::error::Result;
 use crate::rustypickle::Pickle;
 use serde::Serialize;
 
@@ -6,18 +7,77 @@ pub struct PickleListExtender<'a> {
     pub(crate) list_name: String,
 }
 impl<'a> PickleListExtender<'a> {
-    pub fn ladd<V>(&mut self, value: &V) -> PickleListExtender
+    pub fn ladd<V>(&mut self, value: &V) -> PickleListExtender<'_>
     where
         V: Serialize,
     {
         self.db.ladd(&self.list_name, value).unwrap()
     }
 
-    pub fn lextend<'i, V, I>(&mut self, seq: I) -> PickleListExtender
+    pub fn lextend<'i, V, I>(&mut self, seq: I) -> PickleListExtender<'_>
     where
         V: 'i + Serialize,
         I: IntoIterator<Item = &'i V>,
     {
         self.db.lextend(&self.list_name, seq).unwrap()
     }
+
+    pub fn linsert<V>(&mut self, pos: isize, value: &V) -> Result<PickleListExtender<'_>>
+    where
+        V: Serialize,
+    {
+        self.db.linsert(&self.list_name, pos, value)
+    }
+
+    pub fn lset<V>(&mut self, pos: isize, value: &V) -> Result<PickleListExtender<'_>>
+    where
+        V: Serialize,
+    {
+        self.db.lset(&self.list_name, pos, value)
+    }
+
+    /// Remove the item in position `pos` and continue the chain. The removed item is discarded; use
+    /// [Pickle::lpop()](struct.Pickle.html#method.lpop) to get its value.
+    pub fn lpop(&mut self, pos: isize) -> Result<PickleListExtender<'_>> {
+        let index = self.db.list_index(&self.list_name, pos, false)?;
+        self.db.lpop_raw(&self.list_name, index)?;
+        Ok(PickleListExtender {
+            db: self.db,
+            list_name: self.list_name.clone(),
+        })
+    }
+
+    /// Remove the first occurrence of `value`, if any, and continue the chain.
+    pub fn lremvalue<V>(&mut self, value: &V) -> Result<PickleListExtender<'_>>
+    where
+        V: Serialize,
+    {
+        self.db.lremvalue(&self.list_name, value)?;
+        Ok(PickleListExtender {
+            db: self.db,
+            list_name: self.list_name.clone(),
+        })
+    }
+}
+
+pub struct PickleDictExtender<'a> {
+    pub(crate) db: &'a mut Pickle,
+    pub(crate) dict_name: String,
+}
+impl<'a> PickleDictExtender<'a> {
+    pub fn dadd<V>(&mut self, key: &str, value: &V) -> Result<PickleDictExtender<'_>>
+    where
+        V: Serialize,
+    {
+        self.db.dadd(&self.dict_name, key, value)
+    }
+
+    /// Merge the pairs of the dict `other` into this dict and continue the chain.
+    pub fn dmerge(&mut self, other: &str) -> Result<PickleDictExtender<'_>> {
+        self.db.dmerge(&self.dict_name, other)?;
+        Ok(PickleDictExtender {
+            db: self.db,
+            dict_name: self.dict_name.clone(),
+        })
+    }
 }
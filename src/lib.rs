@@ -1,7 +1,35 @@
-pub use self::rustypickle::{DumpPolicy, Pickle};
-pub use self::serialization::SerializationMethod;
+#[cfg(feature = "tokio")]
+pub use self::async_pickle::AsyncPickle;
+pub use self::batch::PickleBatch;
+pub use self::extenders::{PickleDictExtender, PickleListExtender};
+pub use self::periodic::PeriodicPickle;
+pub use self::rustypickle::{
+    CompactionThreshold, DropPolicy, DumpPolicy, Durability, LockMode, Pickle,
+};
+#[cfg(feature = "bincode")]
+pub use self::serialization::BincodeSerializer;
+#[cfg(feature = "cbor")]
+pub use self::serialization::CborSerializer;
+#[cfg(feature = "msgpack")]
+pub use self::serialization::MsgPackSerializer;
+#[cfg(feature = "yaml")]
+pub use self::serialization::YamlSerializer;
+pub use self::serialization::{
+    feed_sink, DataSink, DbContents, DbContentsRef, DbDictMap, DbExpiryMap, DbListMap, DbMap,
+    JsonSerializer, PickleDbSerializer, PickleSerializer, SerializationMethod,
+};
+pub use self::shared::SharedPickle;
+pub use erased_serde;
 
+mod append_log;
+#[cfg(feature = "tokio")]
+mod async_pickle;
+mod batch;
 pub mod error;
 mod extenders;
+#[cfg(feature = "parquet")]
+mod parquet_io;
+mod periodic;
 mod rustypickle;
 mod serialization;
+mod shared;
@@ -0,0 +1,160 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::writer::SerializedFileWriter;
+use parquet::record::Field;
+use parquet::schema::parser::parse_message_type;
+
+use crate::error::{Error, ErrorCode, Result};
+
+// The table written by Pickle::export_parquet(). `index` is null for plain values and holds the position
+// of the item for lists. Every row has either `value_json` or, for serializers that can't be converted to
+// json, `value_bytes`.
+const PARQUET_SCHEMA: &str = "
+    message pickle {
+        REQUIRED BYTE_ARRAY key (UTF8);
+        REQUIRED BYTE_ARRAY kind (UTF8);
+        OPTIONAL INT64 index;
+        OPTIONAL BYTE_ARRAY value_json (UTF8);
+        OPTIONAL BYTE_ARRAY value_bytes;
+    }
+";
+
+pub(crate) const KIND_VALUE: &str = "value";
+pub(crate) const KIND_LIST: &str = "list";
+
+/// A single row of an exported parquet table.
+pub(crate) struct ParquetRow {
+    pub(crate) key: String,
+    pub(crate) kind: &'static str,
+    pub(crate) index: Option<i64>,
+    pub(crate) value: ParquetValue,
+}
+
+/// The value of a row, as json or as the bytes the serializer of the DB wrote.
+pub(crate) enum ParquetValue {
+    Json(String),
+    Bytes(Vec<u8>),
+}
+
+fn parquet_error(err: parquet::errors::ParquetError) -> Error {
+    Error::new(ErrorCode::Serialization(err.to_string()))
+}
+
+pub(crate) fn write_rows<P: AsRef<Path>>(path: P, rows: &[ParquetRow]) -> Result<()> {
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA).map_err(parquet_error)?);
+    let props = Arc::new(WriterProperties::builder().build());
+    let file = match File::create(path) {
+        Ok(file) => file,
+        Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+    };
+
+    let bytes = |column: fn(&ParquetRow) -> Option<&[u8]>| -> (Vec<ByteArray>, Vec<i16>) {
+        let values = rows
+            .iter()
+            .filter_map(|row| column(row).map(|val| ByteArray::from(val.to_vec())))
+            .collect();
+        let levels = rows
+            .iter()
+            .map(|row| column(row).is_some() as i16)
+            .collect();
+        (values, levels)
+    };
+    let (keys, _) = bytes(|row| Some(row.key.as_bytes()));
+    let (kinds, _) = bytes(|row| Some(row.kind.as_bytes()));
+    let (json_values, json_levels) = bytes(|row| match &row.value {
+        ParquetValue::Json(json) => Some(json.as_bytes()),
+        ParquetValue::Bytes(_) => None,
+    });
+    let (byte_values, byte_levels) = bytes(|row| match &row.value {
+        ParquetValue::Json(_) => None,
+        ParquetValue::Bytes(ser_data) => Some(ser_data),
+    });
+    let indexes: Vec<i64> = rows.iter().filter_map(|row| row.index).collect();
+    let index_levels: Vec<i16> = rows.iter().map(|row| row.index.is_some() as i16).collect();
+
+    let mut writer = SerializedFileWriter::new(file, schema, props).map_err(parquet_error)?;
+    let mut row_group = writer.next_row_group().map_err(parquet_error)?;
+    let mut column_index = 0;
+    while let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+        match column_index {
+            0 => column
+                .typed::<ByteArrayType>()
+                .write_batch(&keys, None, None),
+            1 => column
+                .typed::<ByteArrayType>()
+                .write_batch(&kinds, None, None),
+            2 => column
+                .typed::<Int64Type>()
+                .write_batch(&indexes, Some(&index_levels), None),
+            3 => {
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&json_values, Some(&json_levels), None)
+            }
+            _ => {
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&byte_values, Some(&byte_levels), None)
+            }
+        }
+        .map_err(parquet_error)?;
+        column.close().map_err(parquet_error)?;
+        column_index += 1;
+    }
+    row_group.close().map_err(parquet_error)?;
+    writer.close().map_err(parquet_error)?;
+    Ok(())
+}
+
+pub(crate) fn read_rows<P: AsRef<Path>>(path: P) -> Result<Vec<ParquetRow>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+    };
+    let reader = SerializedFileReader::new(file).map_err(parquet_error)?;
+
+    let mut rows = Vec::new();
+    for row in reader.get_row_iter(None).map_err(parquet_error)? {
+        let row = row.map_err(parquet_error)?;
+        let mut key = None;
+        let mut kind = None;
+        let mut index = None;
+        let mut value = None;
+        for (name, field) in row.get_column_iter() {
+            match (name.as_str(), field) {
+                ("key", Field::Str(val)) => key = Some(val.to_string()),
+                ("kind", Field::Str(val)) if val == KIND_VALUE => kind = Some(KIND_VALUE),
+                ("kind", Field::Str(val)) if val == KIND_LIST => kind = Some(KIND_LIST),
+                ("index", Field::Long(val)) => index = Some(*val),
+                ("value_json", Field::Str(val)) => {
+                    value = Some(ParquetValue::Json(val.to_string()))
+                }
+                ("value_bytes", Field::Bytes(val)) => {
+                    value = Some(ParquetValue::Bytes(val.data().to_vec()))
+                }
+                _ => (),
+            }
+        }
+
+        match (key, kind, value) {
+            (Some(key), Some(kind), Some(value)) => rows.push(ParquetRow {
+                key,
+                kind,
+                index,
+                value,
+            }),
+            _ => {
+                return Err(Error::new(ErrorCode::Serialization(format!(
+                    "invalid row in parquet file: {}",
+                    row
+                ))))
+            }
+        }
+    }
+    Ok(rows)
+}
@@ -0,0 +1,108 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::rustypickle::{DumpPolicy, Pickle};
+
+// The shortest interval the background thread waits between dumps, so a zero interval doesn't keep it
+// spinning on the lock of the DB.
+const MIN_FLUSH_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A `Pickle` under [DumpPolicy::Periodic] with a background thread that dumps it on the interval of the
+/// policy.
+///
+/// On its own, `DumpPolicy::Periodic` only checks the interval when the DB changes, so the last changes of
+/// a burst stay in memory until the next one. The thread dumps those as soon as the interval is up, and
+/// skips the dump when nothing changed. When the `PeriodicPickle` is dropped the thread is stopped and any
+/// changes left are dumped one last time. With any other dump policy no thread is started. The thread
+/// waits at least 10 milliseconds between dumps, even if the interval of the policy is shorter.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use rusty_pickle::{DumpPolicy, PeriodicPickle, Pickle, SerializationMethod};
+///
+/// let policy = DumpPolicy::Periodic(Duration::from_secs(1));
+/// let db = PeriodicPickle::new(Pickle::new("example.db", policy, SerializationMethod::Json));
+/// db.lock().set("key1", &100).unwrap();
+/// ```
+pub struct PeriodicPickle {
+    db: Arc<Mutex<Pickle>>,
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    flusher: Option<JoinHandle<()>>,
+}
+
+impl PeriodicPickle {
+    /// Start dumping `db` in the background on the interval of its `DumpPolicy::Periodic`.
+    pub fn new(db: Pickle) -> PeriodicPickle {
+        let interval = match db.dump_policy() {
+            DumpPolicy::Periodic(interval) => Some((*interval).max(MIN_FLUSH_INTERVAL)),
+            _ => None,
+        };
+
+        let db = Arc::new(Mutex::new(db));
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let flusher = interval.map(|interval| {
+            let db = Arc::clone(&db);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || flush_periodically(&db, &stop, interval))
+        });
+
+        PeriodicPickle { db, stop, flusher }
+    }
+
+    /// Lock the DB to read or change it. The background thread waits for the lock to be released.
+    pub fn lock(&self) -> MutexGuard<'_, Pickle> {
+        self.db.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Stop the background thread and dump any changes that are left.
+    ///
+    /// This is what dropping the `PeriodicPickle` does as well, except that an error of the last dump is
+    /// returned instead of being lost.
+    pub fn close(mut self) -> Result<()> {
+        self.stop_flusher()
+    }
+
+    fn stop_flusher(&mut self) -> Result<()> {
+        let flusher = match self.flusher.take() {
+            Some(flusher) => flusher,
+            None => return Ok(()),
+        };
+
+        let (stopped, wakeup) = &*self.stop;
+        *stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        wakeup.notify_one();
+        let _ = flusher.join();
+
+        self.lock().dump_pending()
+    }
+}
+
+impl Drop for PeriodicPickle {
+    fn drop(&mut self) {
+        let _ = self.stop_flusher();
+    }
+}
+
+// A failed dump leaves the changes pending, so it's retried on the next interval.
+fn flush_periodically(db: &Mutex<Pickle>, stop: &(Mutex<bool>, Condvar), interval: Duration) {
+    let (stopped, wakeup) = stop;
+    loop {
+        let guard = stopped.lock().unwrap_or_else(PoisonError::into_inner);
+        let (guard, _) = wakeup
+            .wait_timeout_while(guard, interval, |stopped| !*stopped)
+            .unwrap_or_else(PoisonError::into_inner);
+        if *guard {
+            return;
+        }
+        drop(guard);
+
+        let _ = db
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .dump_pending();
+    }
+}
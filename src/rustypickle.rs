@@ -1,28 +1,227 @@
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use crate::append_log;
+use crate::batch::PickleBatch;
 use crate::error::{Error, ErrorCode, Result};
-use crate::extenders::PickleListExtender;
-use crate::serialization::SerializationMethod;
+use crate::extenders::{PickleDictExtender, PickleListExtender};
+#[cfg(feature = "parquet")]
+use crate::parquet_io::{self, ParquetRow, ParquetValue, KIND_LIST, KIND_VALUE};
 use crate::serialization::Serializer;
+use crate::serialization::{PickleSerializer, SerializationMethod};
 
 pub enum DumpPolicy {
     Never,
     Auto,
     UponRequest,
     Periodic(Duration),
+    /// Every change is appended as a record to a log file next to the DB file, named like the DB file with
+    /// a `.log` suffix, instead of rewriting the whole DB. A record holds the new state of the changed key,
+    /// so its cost depends on the size of that value, list or dict and not on the size of the DB.
+    /// [Pickle::load()](struct.Pickle.html#method.load) replays the log over the DB file, and
+    /// [Pickle::dump()](struct.Pickle.html#method.dump) writes a full DB file and empties the log.
+    AppendLog,
+}
+
+/// The lock a `Pickle` holds on its DB file to keep other `Pickle`s, in this process or in others, from
+/// writing it at the same time, see [Pickle::load_locked()](struct.Pickle.html#method.load_locked).
+///
+/// The lock is an advisory lock on a file next to the DB file, named like the DB file with a `.lock`
+/// suffix, and it's released when the `Pickle` is dropped. Only `Pickle`s that take a lock respect it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// An exclusive lock for the one `Pickle` that writes the DB. Waits until no other lock is held.
+    Exclusive,
+    /// A shared lock for `Pickle`s that only read the DB, which can be held by any number of them at once.
+    /// Waits until no exclusive lock is held. Writing the DB fails with
+    /// [ErrorType::Locked](error/enum.ErrorType.html#variant.Locked).
+    Shared,
+    /// Like `Exclusive`, but fails right away with
+    /// [ErrorType::Locked](error/enum.ErrorType.html#variant.Locked) instead of waiting.
+    TryExclusive,
+    /// Like `Shared`, but fails right away with
+    /// [ErrorType::Locked](error/enum.ErrorType.html#variant.Locked) instead of waiting.
+    TryShared,
+}
+
+impl LockMode {
+    fn is_shared(&self) -> bool {
+        matches!(self, LockMode::Shared | LockMode::TryShared)
+    }
+}
+
+/// What a `Pickle` does with changes that aren't in the DB file yet when it's dropped, see
+/// [Pickle::set_drop_policy()](struct.Pickle.html#method.set_drop_policy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// Drop the changes.
+    Discard,
+    /// Dump the DB, if it has any pending changes and its [DumpPolicy] isn't `Never`.
+    DumpPending,
+}
+
+/// When a DB under [DumpPolicy::AppendLog] compacts its log on its own, see
+/// [Pickle::set_compaction_threshold()](struct.Pickle.html#method.set_compaction_threshold).
+#[derive(Debug, Clone, Copy)]
+pub enum CompactionThreshold {
+    /// The log is only compacted by calling [Pickle::compact()](struct.Pickle.html#method.compact).
+    Never,
+    /// Compact once the log is larger than this many bytes.
+    LogBytes(u64),
+    /// Compact once the log is larger than this many times the size of the DB file.
+    SnapshotRatio(f64),
+}
+
+/// How hard [Pickle::dump()](struct.Pickle.html#method.dump) and
+/// [DumpPolicy::AppendLog] try to make sure that written data survives a crash of the OS or a power loss,
+/// see [Pickle::set_durability()](struct.Pickle.html#method.set_durability).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Durability {
+    /// Leave it to the OS to flush written data to disk.
+    NoSync,
+    /// Flush the DB file, and every record appended to the log, to disk before moving on.
+    SyncFile,
+    /// Like `SyncFile`, and also flush the directory of the DB file so renaming the new DB file into
+    /// place is on disk as well.
+    SyncFileAndDir,
+}
+
+impl CompactionThreshold {
+    fn is_reached(&self, log_len: u64, snapshot_len: u64) -> bool {
+        match *self {
+            CompactionThreshold::Never => false,
+            CompactionThreshold::LogBytes(max_len) => log_len > max_len,
+            CompactionThreshold::SnapshotRatio(ratio) => {
+                log_len as f64 > snapshot_len as f64 * ratio
+            }
+        }
+    }
 }
 
 pub struct Pickle {
     map: HashMap<String, Vec<u8>>,
     list_map: HashMap<String, Vec<Vec<u8>>>,
+    dict_map: HashMap<String, HashMap<String, Vec<u8>>>,
+    expiry_map: HashMap<String, u64>,
     serializer: Serializer,
     db_file_path: PathBuf,
     dump_policy: DumpPolicy,
     last_dump: Instant,
+    log_file: Option<fs::File>,
+    compaction_threshold: CompactionThreshold,
+    snapshot_len: u64,
+    durability: Durability,
+    lock: Option<(fs::File, LockMode)>,
+    pending_changes: usize,
+    drop_policy: DropPolicy,
+    drop_error_callback: Option<Box<DropErrorCallback>>,
+    staging: Option<Staging>,
+}
+
+type DropErrorCallback = dyn FnMut(Error) + Send + Sync;
+
+// The changes made in a transaction or a batch, which are dumped together once it's done.
+#[derive(Default)]
+struct Staging {
+    keys: Vec<String>,
+    changes: usize,
+    dump_requested: bool,
+    // what the changed keys held before the transaction, `None` for a key that didn't exist; batches
+    // don't roll back, so they don't keep these
+    originals: Option<HashMap<String, Option<KeyEntry>>>,
+}
+
+impl Staging {
+    fn for_transaction() -> Staging {
+        Staging {
+            originals: Some(HashMap::new()),
+            ..Staging::default()
+        }
+    }
+
+    fn stage(&mut self, keys: &[&str]) {
+        for key in keys {
+            self.add_key(key);
+        }
+        self.changes += 1;
+    }
+
+    fn merge(&mut self, other: Staging) {
+        for key in &other.keys {
+            self.add_key(key);
+        }
+        self.changes += other.changes;
+        self.dump_requested |= other.dump_requested;
+        // a key the outer transaction changed first keeps its older original
+        if let (Some(originals), Some(other_originals)) = (self.originals.as_mut(), other.originals)
+        {
+            for (key, original) in other_originals {
+                originals.entry(key).or_insert(original);
+            }
+        }
+    }
+
+    fn add_key(&mut self, key: &str) {
+        if !self.keys.iter().any(|staged| staged == key) {
+            self.keys.push(String::from(key));
+        }
+    }
+}
+
+// Restores the staging of the enclosing transaction or batch once a transaction is over, and rolls back
+// the transaction if its closure panics, see Pickle::transaction().
+struct TransactionGuard<'a> {
+    db: &'a mut Pickle,
+    outer_staging: Option<Option<Staging>>,
+}
+
+impl<'a> TransactionGuard<'a> {
+    fn new(db: &'a mut Pickle) -> TransactionGuard<'a> {
+        let outer_staging = db.staging.replace(Staging::for_transaction());
+        TransactionGuard {
+            db,
+            outer_staging: Some(outer_staging),
+        }
+    }
+
+    // Give back the staging of the transaction, leaving the DB as the closure left it.
+    fn finish(mut self) -> Staging {
+        let staging = self.db.staging.take().unwrap_or_default();
+        self.db.staging = self.outer_staging.take().unwrap();
+        staging
+    }
+}
+
+impl Drop for TransactionGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(outer_staging) = self.outer_staging.take() {
+            let staging = self.db.staging.take();
+            self.db.staging = outer_staging;
+            if let Some(staging) = staging {
+                self.db.roll_back(staging);
+            }
+        }
+    }
+}
+
+// Everything stored under a key, taken out of the DB to be put under another key.
+#[derive(Clone)]
+struct KeyEntry {
+    data: KeyData,
+    expires_at: Option<u64>,
+}
+
+#[derive(Clone)]
+enum KeyData {
+    Value(Vec<u8>),
+    List(Vec<Vec<u8>>),
+    Dict(HashMap<String, Vec<u8>>),
 }
 
 impl Pickle {
@@ -32,7 +231,7 @@ impl Pickle {
     ///
     /// * `db_path` - a path where the DB will be stored
     /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file. Please see
-    ///    [Pickle::load()](#method.load) to understand the different policy options
+    ///   [Pickle::load()](#method.load) to understand the different policy options
     /// * `serialization_method` - the serialization method to use for storing the data to memory and file
     ///
     /// # Examples
@@ -40,13 +239,41 @@ impl Pickle {
     /// ```no_run
     /// use rusty_pickle::{Pickle, DumpPolicy, SerializationMethod};
     ///
-    /// let mut db = Pickle::new("example.db", DbDumpPolicy::AutoDump, SerializationMethod::Json);
+    /// let mut db = Pickle::new("example.db", DumpPolicy::Auto, SerializationMethod::Json);
     /// ```
     ///
     pub fn new<P: AsRef<Path>>(
         db_path: P,
         dump_policy: DumpPolicy,
         serialization_method: SerializationMethod,
+    ) -> Pickle {
+        Pickle::new_with(db_path, dump_policy, Serializer::new(serialization_method))
+    }
+
+    /// Constructs a new `Pickle` instance that uses a custom serializer.
+    ///
+    /// This is the same as [Pickle::new()](#method.new), but instead of one of the built-in
+    /// [SerializationMethod](enum.SerializationMethod.html)s, values and the DB file are serialized by any
+    /// implementation of [PickleSerializer](trait.PickleSerializer.html).
+    ///
+    /// # Arguments
+    ///
+    /// * `db_path` - a path where the DB will be stored
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file
+    /// * `serializer` - the serializer to use for storing the data to memory and file
+    ///
+    pub fn with_serializer<P: AsRef<Path>>(
+        db_path: P,
+        dump_policy: DumpPolicy,
+        serializer: Box<dyn PickleSerializer>,
+    ) -> Pickle {
+        Pickle::new_with(db_path, dump_policy, Serializer::with(serializer))
+    }
+
+    fn new_with<P: AsRef<Path>>(
+        db_path: P,
+        dump_policy: DumpPolicy,
+        serializer: Serializer,
     ) -> Pickle {
         let mut db_path_buf = PathBuf::new();
         db_path_buf.push(db_path);
@@ -54,10 +281,21 @@ impl Pickle {
         Pickle {
             map: HashMap::new(),
             list_map: HashMap::new(),
-            serializer: Serializer::new(serialization_method),
+            dict_map: HashMap::new(),
+            expiry_map: HashMap::new(),
+            serializer,
             db_file_path: db_path_buf,
             dump_policy,
             last_dump: Instant::now(),
+            log_file: None,
+            compaction_threshold: CompactionThreshold::Never,
+            snapshot_len: 0,
+            durability: Durability::NoSync,
+            lock: None,
+            pending_changes: 0,
+            drop_policy: DropPolicy::DumpPending,
+            drop_error_callback: None,
+            staging: None,
         }
     }
 
@@ -66,28 +304,156 @@ impl Pickle {
         dump_policy: DumpPolicy,
         serialization_method: SerializationMethod,
     ) -> Result<Pickle> {
+        Pickle::load_with(
+            db_path,
+            dump_policy,
+            Serializer::new(serialization_method),
+            None,
+        )
+    }
+
+    /// Load a DB from a file that was written with a custom serializer.
+    ///
+    /// This is the same as [Pickle::load()](#method.load), but the file is read by any implementation of
+    /// [PickleSerializer](trait.PickleSerializer.html), which is then used for the loaded DB as well.
+    ///
+    pub fn load_with_serializer<P: AsRef<Path>>(
+        db_path: P,
+        dump_policy: DumpPolicy,
+        serializer: Box<dyn PickleSerializer>,
+    ) -> Result<Pickle> {
+        Pickle::load_with(db_path, dump_policy, Serializer::with(serializer), None)
+    }
+
+    /// Load a DB from a file while holding a lock on it.
+    ///
+    /// This is the same as [Pickle::load()](#method.load), but the lock is taken before the file is read and
+    /// is held until the `Pickle` is dropped, so no other `Pickle` that takes a lock can write the DB in the
+    /// meantime. See [LockMode](enum.LockMode.html) for the kinds of locks.
+    ///
+    /// # Arguments
+    ///
+    /// * `db_path` - the path of the DB file to load
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file
+    /// * `serialization_method` - the serialization method used to store the data in the file
+    /// * `lock_mode` - the kind of lock to take on the DB file
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rusty_pickle::{DumpPolicy, LockMode, Pickle, SerializationMethod};
+    ///
+    /// let db = Pickle::load_locked(
+    ///     "example.db",
+    ///     DumpPolicy::Auto,
+    ///     SerializationMethod::Json,
+    ///     LockMode::TryExclusive,
+    /// );
+    /// ```
+    ///
+    pub fn load_locked<P: AsRef<Path>>(
+        db_path: P,
+        dump_policy: DumpPolicy,
+        serialization_method: SerializationMethod,
+        lock_mode: LockMode,
+    ) -> Result<Pickle> {
+        Pickle::load_with(
+            db_path,
+            dump_policy,
+            Serializer::new(serialization_method),
+            Some(lock_mode),
+        )
+    }
+
+    /// Constructs a new `Pickle` instance that holds a lock on its DB file.
+    ///
+    /// This is the same as [Pickle::new()](#method.new), with the lock of
+    /// [Pickle::load_locked()](#method.load_locked).
+    ///
+    /// # Arguments
+    ///
+    /// * `db_path` - a path where the DB will be stored
+    /// * `dump_policy` - an enum value that determines the policy of dumping DB changes into the file
+    /// * `serialization_method` - the serialization method to use for storing the data to memory and file
+    /// * `lock_mode` - the kind of lock to take on the DB file
+    ///
+    pub fn new_locked<P: AsRef<Path>>(
+        db_path: P,
+        dump_policy: DumpPolicy,
+        serialization_method: SerializationMethod,
+        lock_mode: LockMode,
+    ) -> Result<Pickle> {
+        let lock = lock_db_file(db_path.as_ref(), lock_mode)?;
+        let mut db = Pickle::new(db_path, dump_policy, serialization_method);
+        db.lock = Some((lock, lock_mode));
+        Ok(db)
+    }
+
+    fn load_with<P: AsRef<Path>>(
+        db_path: P,
+        dump_policy: DumpPolicy,
+        serializer: Serializer,
+        lock_mode: Option<LockMode>,
+    ) -> Result<Pickle> {
+        let lock = match lock_mode {
+            Some(lock_mode) => Some((lock_db_file(db_path.as_ref(), lock_mode)?, lock_mode)),
+            None => None,
+        };
+
         let content = match fs::read(db_path.as_ref()) {
             Ok(file_content) => file_content,
             Err(err) => return Err(Error::new(ErrorCode::Io(err))),
         };
 
-        let serializer = Serializer::new(serialization_method);
-
-        let maps_from_file: (_, _) = match serializer.deserialize_db(&content) {
-            Ok(maps) => maps,
+        let mut db_from_file = match serializer.deserialize_db(&content) {
+            Ok(db) => db,
             Err(err_str) => return Err(Error::new(ErrorCode::Serialization(err_str))),
         };
 
         let mut db_path_buf = PathBuf::new();
         db_path_buf.push(db_path);
 
+        // changes made under DumpPolicy::AppendLog since the DB file was written
+        let log_file_path = log_file_path(&db_path_buf);
+        let log_len = match fs::read(&log_file_path) {
+            Ok(log) => match append_log::replay(&serializer, &log, &mut db_from_file) {
+                Ok(log_len) => log_len,
+                Err(err_str) => return Err(Error::new(ErrorCode::Serialization(err_str))),
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+            Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+        };
+
+        let log_file = match dump_policy {
+            DumpPolicy::AppendLog => Some(open_log_file(&log_file_path, log_len as u64)?),
+            _ => None,
+        };
+
+        // an expiry time only counts for a key that exists
+        db_from_file.expiry_map.retain(|key, _| {
+            db_from_file.map.contains_key(key)
+                || db_from_file.list_map.contains_key(key)
+                || db_from_file.dict_map.contains_key(key)
+        });
+
         Ok(Pickle {
-            map: maps_from_file.0,
-            list_map: maps_from_file.1,
+            map: db_from_file.map,
+            list_map: db_from_file.list_map,
+            dict_map: db_from_file.dict_map,
+            expiry_map: db_from_file.expiry_map,
             serializer,
             db_file_path: db_path_buf,
             dump_policy,
             last_dump: Instant::now(),
+            log_file,
+            compaction_threshold: CompactionThreshold::Never,
+            snapshot_len: content.len() as u64,
+            durability: Durability::NoSync,
+            lock,
+            pending_changes: 0,
+            drop_policy: DropPolicy::DumpPending,
+            drop_error_callback: None,
+            staging: None,
         })
     }
 
@@ -113,8 +479,11 @@ impl Pickle {
     where
         V: DeserializeOwned,
     {
+        if self.is_expired(key) {
+            return None;
+        }
         match self.map.get(key) {
-            Some(val) => self.serializer.deserialize_data::<V>(&val),
+            Some(val) => self.serializer.deserialize_data::<V>(val),
             None => None,
         }
     }
@@ -130,16 +499,37 @@ impl Pickle {
     where
         V: Serialize,
     {
-        if self.list_map.contains_key(key) {
-            self.list_map.remove(key);
+        let ser_data = self.serialize_value(value)?;
+        self.set_raw(key, ser_data)
+    }
+
+    /// Serialize a value the way [Pickle::set()](#method.set) stores it.
+    pub(crate) fn serialize_value<V>(&self, value: &V) -> Result<Vec<u8>>
+    where
+        V: Serialize,
+    {
+        match self.serializer.serialize_data(value) {
+            Ok(data) => Ok(data),
+            Err(err_str) => Err(Error::new(ErrorCode::Serialization(err_str))),
         }
-        let ser_data = match self.serializer.serialize_data(value) {
-            Ok(data) => data,
-            Err(err_str) => return Err(Error::new(ErrorCode::Serialization(err_str))),
-        };
+    }
+
+    /// Set a key to a value that was already serialized by
+    /// [Pickle::serialize_value()](#method.serialize_value).
+    pub(crate) fn set_raw(&mut self, key: &str, ser_data: Vec<u8>) -> Result<()> {
+        self.set_entry(key, ser_data, None)
+    }
 
+    fn set_entry(&mut self, key: &str, ser_data: Vec<u8>, expires_at: Option<u64>) -> Result<()> {
+        self.begin_change(key);
+        let original_expiry = match expires_at {
+            Some(expires_at) => self.expiry_map.insert(String::from(key), expires_at),
+            None => self.expiry_map.remove(key),
+        };
+        let original_list = self.list_map.remove(key);
+        let original_dict = self.dict_map.remove(key);
         let original_value = self.map.insert(String::from(key), ser_data);
-        match self.dumpdb() {
+        match self.dumpdb(&[key]) {
             Ok(_) => Ok(()),
             Err(err) => {
                 match original_value {
@@ -150,157 +540,1312 @@ impl Pickle {
                         self.map.insert(String::from(key), orig_value.to_vec());
                     }
                 }
-
-                Err(err)
-            }
-        }
-    }
-
-    pub fn dump(&mut self) -> Result<()> {
-        if let DumpPolicy::Never = self.dump_policy {
-            return Ok(());
-        }
-
-        match self.serializer.serialize_db(&self.map, &self.list_map) {
-            Ok(ser_db) => {
-                let temp_file_path = format!(
-                    "{}.temp.{}",
-                    self.db_file_path.to_str().unwrap(),
-                    SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs()
-                );
-
-                match fs::write(&temp_file_path, ser_db) {
-                    Ok(_) => (),
-                    Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+                if let Some(orig_list) = original_list {
+                    self.list_map.insert(String::from(key), orig_list);
                 }
-
-                match fs::rename(temp_file_path, &self.db_file_path) {
-                    Ok(_) => (),
-                    Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+                if let Some(orig_dict) = original_dict {
+                    self.dict_map.insert(String::from(key), orig_dict);
                 }
+                self.restore_expiry(key, original_expiry);
 
-                if let DumpPolicy::Periodic(_dur) = self.dump_policy {
-                    self.last_dump = Instant::now();
-                }
-                Ok(())
+                Err(err)
             }
-            Err(err_str) => Err(Error::new(ErrorCode::Serialization(err_str))),
         }
-    } // end dump method
+    }
 
-    fn dumpdb(&mut self) -> Result<()> {
-        match self.dump_policy {
-            DumpPolicy::Auto => self.dump(),
-            DumpPolicy::Periodic(duration) => {
-                let now = Instant::now();
-                if now.duration_since(self.last_dump) > duration {
-                    self.last_dump = Instant::now();
-                    self.dump()?;
-                }
-                Ok(())
-            }
-            _ => Ok(()),
+    /// Set a key and its respective value, only if the key doesn't exist yet.
+    ///
+    /// A key that holds a list or a dict exists as well, and an expired key doesn't. Returns `true` if the
+    /// value was set. If dumping the DB fails, the key is removed again and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A string that a value would be associated with
+    /// * `value` - A piece of data to be stored
+    ///
+    /// # Examples
+    ///
+    /// // claim a job, unless another worker already did
+    /// let claimed = db.set_if_absent("job1", &"worker1").unwrap();
+    ///
+    pub fn set_if_absent<V>(&mut self, key: &str, value: &V) -> Result<bool>
+    where
+        V: Serialize,
+    {
+        if self.exists(key) {
+            return Ok(false);
         }
+        self.set(key, value)?;
+        Ok(true)
     }
 
-    /// Check if a key exists.
+    /// Set a key and its respective value, only if the key already exists, as a value, a list or a dict.
     ///
-    /// This method returns `true` if the key exists and `false` otherwise.
+    /// The new value replaces what the key held just like [Pickle::set()](#method.set) does. Returns `true`
+    /// if the value was set.
     ///
     /// # Arguments
     ///
-    /// * `key` - the key to check
+    /// * `key` - A string that a value would be associated with
+    /// * `value` - A piece of data to be stored
     ///
-    pub fn exists(&self, key: &str) -> bool {
-        self.map.get(key).is_some() || self.list_map.get(key).is_some()
+    pub fn set_if_present<V>(&mut self, key: &str, value: &V) -> Result<bool>
+    where
+        V: Serialize,
+    {
+        if !self.exists(key) {
+            return Ok(false);
+        }
+        self.set(key, value)?;
+        Ok(true)
     }
 
-    /// Return the count of keys in the database
+    /// Set a key and its respective value, and return the value it had before, or `None` if it didn't
+    /// exist.
     ///
-    /// This method returns a `usize` of the number of keys in the database
+    /// If the old value can't be deserialized into `V`, or the key holds a list or a dict, an error of type
+    /// [WrongType](error/enum.ErrorType.html#variant.WrongType) is returned and nothing is set. If dumping
+    /// the DB fails, the old value is restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A string that a value would be associated with
+    /// * `value` - A piece of data to be stored
     ///
     /// # Examples
     ///
-    /// let count = db.key_count();
+    /// // reset a counter and get its last value
+    /// let count = db.getset("counter", &0).unwrap();
     ///
-    pub fn key_count(&self) -> usize {
-        // the latter addition is moot until the methods are added
-        self.map.iter().len() + self.list_map.iter().len()
+    pub fn getset<V>(&mut self, key: &str, value: &V) -> Result<Option<V>>
+    where
+        V: Serialize + DeserializeOwned,
+    {
+        let old_value = self.typed_value::<V>(key, "of the requested type")?;
+        self.set(key, value)?;
+        Ok(old_value)
     }
 
-    /// Return a vector of keys in the database
+    /// Set a key to a new value, only if its current value is `expected`.
+    ///
+    /// `expected` is serialized and compared with the stored bytes of the value, so it must be serialized
+    /// the same way the value was; e.g. the order of a `HashMap` may differ between serializations. A
+    /// missing or expired key, or a key holding a list or a dict, never matches. Returns `true` if the
+    /// value was swapped. If dumping the DB fails, the old value is restored and the error is returned.
     ///
+    /// # Arguments
+    ///
+    /// * `key` - the key of the value
+    /// * `expected` - the value the key must have
+    /// * `new` - the value to set
     ///
     /// # Examples
     ///
-    /// let key_array = db.list_keys()
+    /// // move a job from one state to the next, unless another worker did already
+    /// let swapped = db.compare_and_swap("job1", &"queued", &"running").unwrap();
     ///
-    pub fn list_keys(&self) -> Vec<String> {
-        let mut key_array: Vec<String> = Vec::new();
-        for k in self.map.keys() {
-            key_array.push(k.to_string());
+    pub fn compare_and_swap<E, V>(&mut self, key: &str, expected: &E, new: &V) -> Result<bool>
+    where
+        E: Serialize,
+        V: Serialize,
+    {
+        let expected = self.serialize_value(expected)?;
+        let matches = !self.is_expired(key) && self.map.get(key) == Some(&expected);
+        if !matches {
+            return Ok(false);
         }
-        key_array
+        self.set(key, new)?;
+        Ok(true)
     }
 
-    /// Remove a key-value pair or a list from the DB.
+    /// Set a key and its respective value, which expires after a while.
+    ///
+    /// Once the time to live is up the key is gone: reading methods behave as if it didn't exist, and it's
+    /// left out of the DB file on the next dump. The expiry time is stored in the DB file, so it holds
+    /// after the DB is loaded again. Setting the key again without a time to live makes it permanent.
     ///
-    /// This methods returns `Ok(true)` if the key was found in the DB or `Ok(false)` if it wasn't found.
     /// # Arguments
     ///
-    /// * `key` the key or list name to remove
+    /// * `key` - A string that a value would be associated with
+    /// * `value` - A piece of data to be stored
+    /// * `ttl` - how long the key lives
     ///
-    pub fn remove(&mut self, key: &str) -> Result<bool> {
-        let remove_map = match self.map.remove(key) {
-            None => None,
-            Some(val) => match self.dumpdb() {
-                Ok(_) => Some(val),
-                Err(err) => {
-                    self.map.insert(String::from(key), val);
-                    return Err(err);
-                }
-            },
-        };
-
-        let remove_list = match self.list_map.remove(key) {
-            None => None,
-            Some(list) => match self.dumpdb() {
-                Ok(_) => Some(list),
-                Err(err) => {
-                    self.list_map.insert(String::from(key), list);
-                    return Err(err);
-                }
-            },
-        };
-
-        Ok(remove_map.is_some() || remove_list.is_some())
-    }
-
-    pub fn ladd<V>(&mut self, name: &str, value: &V) -> Option<PickleListExtender>
+    /// # Examples
+    ///
+    /// // keep a token for an hour
+    /// db.set_with_ttl("token", &String::from("abc"), Duration::from_secs(3600)).unwrap();
+    ///
+    pub fn set_with_ttl<V>(&mut self, key: &str, value: &V, ttl: Duration) -> Result<()>
     where
         V: Serialize,
     {
-        self.lextend(name, &[value])
+        let ser_data = self.serialize_value(value)?;
+        self.set_entry(key, ser_data, Some(expiry_time(ttl)))
     }
 
-    pub fn lextend<'a, V, I>(&mut self, name: &str, seq: I) -> Option<PickleListExtender>
-    where
-        V: 'a + Serialize,
-        I: IntoIterator<Item = &'a V>,
-    {
-        let serializer = &self.serializer;
-        match self.list_map.get_mut(name) {
-            Some(list) => {
+    /// Make an existing value, list or dict expire after a while, see
+    /// [Pickle::set_with_ttl()](#method.set_with_ttl). An earlier expiry time of the key is replaced.
+    ///
+    /// Returns `Ok(false)` if the key doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key, list name or dict name that will expire
+    /// * `ttl` - how long the key lives from now on
+    ///
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> Result<bool> {
+        if !self.exists(key) {
+            return Ok(false);
+        }
+
+        self.begin_change(key);
+        let original_expiry = self.expiry_map.insert(String::from(key), expiry_time(ttl));
+        match self.dumpdb(&[key]) {
+            Ok(_) => Ok(true),
+            Err(err) => {
+                self.restore_expiry(key, original_expiry);
+                Err(err)
+            }
+        }
+    }
+
+    /// Get the time a key has left before it expires.
+    ///
+    /// Returns `None` if the key doesn't exist or doesn't expire.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key, list name or dict name to look at
+    ///
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        let expires_at = *self.expiry_map.get(key)?;
+        match expires_at.checked_sub(now_millis()) {
+            Some(ttl) if ttl > 0 => Some(Duration::from_millis(ttl)),
+            _ => None,
+        }
+    }
+
+    /// Keep a key from expiring.
+    ///
+    /// Returns `Ok(true)` if the key was going to expire and `Ok(false)` if it doesn't exist or doesn't
+    /// expire.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key, list name or dict name to keep
+    ///
+    pub fn persist(&mut self, key: &str) -> Result<bool> {
+        self.begin_change(key);
+        let original_expiry = match self.expiry_map.remove(key) {
+            Some(expires_at) => expires_at,
+            None => return Ok(false),
+        };
+
+        match self.dumpdb(&[key]) {
+            Ok(_) => Ok(true),
+            Err(err) => {
+                self.expiry_map.insert(String::from(key), original_expiry);
+                Err(err)
+            }
+        }
+    }
+
+    /// Increment the integer stored under a key and return the new value.
+    ///
+    /// A key that doesn't exist starts at zero. The value is read as an `i64`, and if it can't be, or the
+    /// key holds a list or a dict, an error of type [WrongType](error/enum.ErrorType.html#variant.WrongType)
+    /// is returned. An increment past the bounds of `i64` returns an error of type
+    /// [Overflow](error/enum.ErrorType.html#variant.Overflow). The change is dumped like any other, and a
+    /// key that expires keeps its expiry time. Formats that don't store the type of a value, like
+    /// `SerializationMethod::Bin`, only tell values apart by their size, so any other value of exactly 8
+    /// bytes, like an `f64` or an empty list, is read as an integer there.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key of the counter
+    /// * `delta` - the amount to add, which may be negative
+    ///
+    /// # Examples
+    ///
+    /// // count a request
+    /// let requests = db.incr("requests", 1).unwrap();
+    ///
+    pub fn incr(&mut self, key: &str, delta: i64) -> Result<i64> {
+        let value = self
+            .typed_value::<i64>(key, "an integer")?
+            .unwrap_or_default();
+        match value.checked_add(delta) {
+            Some(value) => self.set_keeping_expiry(key, value),
+            None => Err(counter_overflow(key, value, delta)),
+        }
+    }
+
+    /// Decrement the integer stored under a key and return the new value, see
+    /// [Pickle::incr()](#method.incr).
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key of the counter
+    /// * `delta` - the amount to subtract, which may be negative
+    ///
+    pub fn decr(&mut self, key: &str, delta: i64) -> Result<i64> {
+        let value = self
+            .typed_value::<i64>(key, "an integer")?
+            .unwrap_or_default();
+        match value.checked_sub(delta) {
+            Some(value) => self.set_keeping_expiry(key, value),
+            None => Err(counter_overflow(key, value, -(delta as i128))),
+        }
+    }
+
+    /// Increment the number stored under a key as an `f64` and return the new value, see
+    /// [Pickle::incr()](#method.incr). A result that isn't finite returns an error of type
+    /// [Overflow](error/enum.ErrorType.html#variant.Overflow).
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key of the counter
+    /// * `delta` - the amount to add, which may be negative
+    ///
+    pub fn incr_float(&mut self, key: &str, delta: f64) -> Result<f64> {
+        let value = self
+            .typed_value::<f64>(key, "a number")?
+            .unwrap_or_default();
+        let result = value + delta;
+        match result.is_finite() {
+            true => self.set_keeping_expiry(key, result),
+            false => Err(counter_overflow(key, value, delta)),
+        }
+    }
+
+    /// Append a string to the string stored under a key and return the new length of the value, in
+    /// characters.
+    ///
+    /// A key that doesn't exist starts as an empty string. If the value isn't a string, or the key holds a
+    /// list or a dict, an error of type [WrongType](error/enum.ErrorType.html#variant.WrongType) is
+    /// returned. The change is dumped like any other, and a key that expires keeps its expiry time. Formats
+    /// that don't store the type of a value, like `SerializationMethod::Bin`, read a list of bytes as a
+    /// string as long as it's valid utf8.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key of the string
+    /// * `more` - the string to append
+    ///
+    /// # Examples
+    ///
+    /// // add a line to a log message
+    /// let len = db.append("message", "\nanother line").unwrap();
+    ///
+    pub fn append(&mut self, key: &str, more: &str) -> Result<usize> {
+        let mut value = self
+            .typed_value::<String>(key, "a string")?
+            .unwrap_or_default();
+        value.push_str(more);
+        let len = value.chars().count();
+        self.set_keeping_expiry(key, value)?;
+        Ok(len)
+    }
+
+    /// Get the length of the string stored under a key, in characters.
+    ///
+    /// A key that doesn't exist has a length of 0. If the value isn't a string, or the key holds a list or
+    /// a dict, an error of type [WrongType](error/enum.ErrorType.html#variant.WrongType) is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key of the string
+    ///
+    pub fn strlen(&self, key: &str) -> Result<usize> {
+        let value = self.typed_value::<String>(key, "a string")?;
+        Ok(value.map_or(0, |value| value.chars().count()))
+    }
+
+    /// Get a part of the string stored under a key.
+    ///
+    /// `start` and `end` are character positions and both are included in the result. A negative position
+    /// counts from the end of the string, so `-1` is the last character. Positions past either end of the
+    /// string are moved to it, and if `start` comes after `end` the result is empty, as it is for a key
+    /// that doesn't exist. If the value isn't a string, or the key holds a list or a dict, an error of type
+    /// [WrongType](error/enum.ErrorType.html#variant.WrongType) is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key of the string
+    /// * `start` - the position of the first character
+    /// * `end` - the position of the last character
+    ///
+    /// # Examples
+    ///
+    /// // get the last 3 characters of a string
+    /// let suffix = db.getrange("key1", -3, -1).unwrap();
+    ///
+    pub fn getrange(&self, key: &str, start: isize, end: isize) -> Result<String> {
+        let value = match self.typed_value::<String>(key, "a string")? {
+            Some(value) => value,
+            None => return Ok(String::new()),
+        };
+        let len = value.chars().count() as isize;
+        let resolve = |pos: isize| if pos < 0 { len + pos } else { pos };
+        let start = resolve(start).max(0);
+        let end = resolve(end).min(len - 1);
+        if start > end {
+            return Ok(String::new());
+        }
+        Ok(value
+            .chars()
+            .skip(start as usize)
+            .take((end - start + 1) as usize)
+            .collect())
+    }
+
+    // Read the value of a key as a `V`, which is an error if it's something else. An expired or missing
+    // key gives `None`.
+    fn typed_value<V>(&self, key: &str, kind: &str) -> Result<Option<V>>
+    where
+        V: DeserializeOwned,
+    {
+        if !self.exists(key) {
+            return Ok(None);
+        }
+        let value = self
+            .map
+            .get(key)
+            .and_then(|ser_data| self.serializer.deserialize_data::<V>(ser_data));
+        match value {
+            Some(value) => Ok(Some(value)),
+            None => Err(wrong_type(key, kind)),
+        }
+    }
+
+    fn set_keeping_expiry<V>(&mut self, key: &str, value: V) -> Result<V>
+    where
+        V: Serialize,
+    {
+        self.purge_if_expired(key);
+        let ser_data = self.serialize_value(&value)?;
+        let expires_at = self.expiry_map.get(key).copied();
+        self.set_entry(key, ser_data, expires_at)?;
+        Ok(value)
+    }
+
+    pub fn dump(&mut self) -> Result<()> {
+        if let Some(staging) = self.staging.as_mut() {
+            staging.dump_requested = true;
+            return Ok(());
+        }
+        self.purge_expired();
+        if let DumpPolicy::Never = self.dump_policy {
+            return Ok(());
+        }
+        self.check_writable()?;
+
+        match self.serializer.serialize_db(
+            &self.map,
+            &self.list_map,
+            &self.dict_map,
+            &self.expiry_map,
+        ) {
+            Ok(ser_db) => {
+                let snapshot_len = ser_db.len() as u64;
+                write_file_atomically(&self.db_file_path, &ser_db, self.durability)?;
+                self.snapshot_len = snapshot_len;
+                self.pending_changes = 0;
+
+                // the DB file now holds every change, so the log starts over
+                let log_file_path = log_file_path(&self.db_file_path);
+                self.log_file = None;
+                match self.dump_policy {
+                    DumpPolicy::AppendLog => {
+                        self.log_file = Some(open_log_file(&log_file_path, 0)?);
+                        // the log may have just been created
+                        if self.durability == Durability::SyncFileAndDir {
+                            sync_dir(&log_file_path)?;
+                        }
+                    }
+                    _ => match fs::remove_file(&log_file_path) {
+                        Ok(_) => (),
+                        Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+                        Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+                    },
+                }
+
+                if let DumpPolicy::Periodic(_dur) = self.dump_policy {
+                    self.last_dump = Instant::now();
+                }
+                Ok(())
+            }
+            Err(err_str) => Err(Error::new(ErrorCode::Serialization(err_str))),
+        }
+    } // end dump method
+
+    /// Compact the log of a DB under [DumpPolicy::AppendLog](enum.DumpPolicy.html#variant.AppendLog).
+    ///
+    /// The whole DB is written to a new DB file, which then replaces the old one, and the log is emptied.
+    /// If the process stops at any point in between, the DB can still be loaded with all of its changes,
+    /// since replaying the log over the new DB file leaves it as is. This is the same as
+    /// [Pickle::dump()](#method.dump).
+    ///
+    pub fn compact(&mut self) -> Result<()> {
+        self.dump()
+    }
+
+    /// Apply several changes all or nothing.
+    ///
+    /// The closure gets the DB to change with all of the usual methods. The changes aren't dumped one by one
+    /// but together once the closure returns, in a single dump or log record as the dump policy says, and a
+    /// call to [Pickle::dump()](#method.dump) in the closure is put off until then as well. If the closure
+    /// returns an error, or dumping the changes fails, the DB is left as it was before the transaction and
+    /// the error is returned; the same goes for a closure that panics. Only the keys the closure changed
+    /// are saved for that, not the whole DB. A transaction inside another one is dumped along with the
+    /// outer one.
+    ///
+    /// # Arguments
+    ///
+    /// * `changes` - a closure that changes the DB and returns an error to roll the changes back. The error
+    ///   can be of any type that an [Error](error/struct.Error.html) converts into
+    ///
+    /// # Examples
+    ///
+    /// // move a value between two keys
+    /// db.transaction(|tx| {
+    ///     let value = tx.get::<i32>("key1").unwrap();
+    ///     tx.remove("key1")?;
+    ///     tx.set("key2", &value)
+    /// }).unwrap();
+    ///
+    pub fn transaction<T, E, F>(&mut self, changes: F) -> std::result::Result<T, E>
+    where
+        E: From<Error>,
+        F: FnOnce(&mut Pickle) -> std::result::Result<T, E>,
+    {
+        let guard = TransactionGuard::new(self);
+        let changed = changes(guard.db);
+        let staging = guard.finish();
+
+        let value = match changed {
+            Ok(value) => value,
+            Err(err) => {
+                self.roll_back(staging);
+                return Err(err);
+            }
+        };
+        match self.staging.as_mut() {
+            Some(outer_staging) => {
+                outer_staging.merge(staging);
+                Ok(value)
+            }
+            None => match self.commit_staged(&staging) {
+                Ok(_) => Ok(value),
+                Err(err) => {
+                    self.roll_back(staging);
+                    Err(E::from(err))
+                }
+            },
+        }
+    }
+
+    /// Make many changes with a single dump.
+    ///
+    /// The returned batch can be used like the DB itself. Its changes aren't dumped one by one but together
+    /// when [PickleBatch::commit()](struct.PickleBatch.html#method.commit) is called or the batch is
+    /// dropped. Unlike a [transaction](#method.transaction), changes that were made stay in the DB when a
+    /// later one fails or the dump fails, in which case they're left for the next dump.
+    ///
+    /// # Examples
+    ///
+    /// let mut batch = db.batch();
+    /// for i in 0..10000 {
+    ///     batch.set(&format!("key{}", i), &i).unwrap();
+    /// }
+    /// batch.commit().unwrap();
+    ///
+    pub fn batch(&mut self) -> PickleBatch<'_> {
+        PickleBatch::new(self)
+    }
+
+    /// Set many keys and their values with a single dump.
+    ///
+    /// The keys are set all or nothing, like in a [transaction](#method.transaction).
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - pairs of a key and the value to set it to
+    ///
+    /// # Examples
+    ///
+    /// db.set_many(vec![("key1", 1), ("key2", 2)]).unwrap();
+    ///
+    pub fn set_many<K, V, I>(&mut self, items: I) -> Result<()>
+    where
+        K: AsRef<str>,
+        V: Serialize,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        self.transaction(|tx| {
+            for (key, value) in items {
+                tx.set(key.as_ref(), &value)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove many keys with a single dump.
+    ///
+    /// The keys are removed all or nothing, like in a [transaction](#method.transaction). Returns the number
+    /// of keys that existed and were removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - the keys to remove
+    ///
+    /// # Examples
+    ///
+    /// let removed = db.remove_many(["key1", "key2"]).unwrap();
+    ///
+    pub fn remove_many<K, I>(&mut self, keys: I) -> Result<usize>
+    where
+        K: AsRef<str>,
+        I: IntoIterator<Item = K>,
+    {
+        self.transaction(|tx| {
+            let mut removed = 0;
+            for key in keys {
+                if tx.remove(key.as_ref())? {
+                    removed += 1;
+                }
+            }
+            Ok(removed)
+        })
+    }
+
+    /// Set when a DB under [DumpPolicy::AppendLog](enum.DumpPolicy.html#variant.AppendLog) compacts its
+    /// log after appending a change. The default is
+    /// [CompactionThreshold::Never](enum.CompactionThreshold.html#variant.Never).
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - the log size at which the log gets compacted
+    ///
+    /// # Examples
+    ///
+    /// // compact once the log grows larger than the DB file
+    /// db.set_compaction_threshold(CompactionThreshold::SnapshotRatio(1.0));
+    ///
+    pub fn set_compaction_threshold(&mut self, threshold: CompactionThreshold) {
+        self.compaction_threshold = threshold;
+    }
+
+    /// Check if the DB has changes that aren't in the DB file yet.
+    ///
+    /// Changes written to the log under [DumpPolicy::AppendLog](enum.DumpPolicy.html#variant.AppendLog)
+    /// count as saved.
+    ///
+    pub fn is_dirty(&self) -> bool {
+        self.pending_changes > 0
+    }
+
+    /// Get the number of changes made since the DB file was last written.
+    ///
+    /// Every successful call that changes the DB counts as one change, and changes that are undone
+    /// because dumping them failed don't count.
+    ///
+    pub fn pending_changes(&self) -> usize {
+        self.pending_changes
+    }
+
+    /// Set what happens to pending changes when the `Pickle` is dropped. The default is
+    /// [DropPolicy::DumpPending](enum.DropPolicy.html#variant.DumpPending), so changes made under
+    /// [DumpPolicy::UponRequest](enum.DumpPolicy.html#variant.UponRequest) or
+    /// [DumpPolicy::Periodic](enum.DumpPolicy.html#variant.Periodic) aren't lost when `dump()` isn't
+    /// called in time. Errors of that last dump go to the callback set with
+    /// [Pickle::on_drop_error()](#method.on_drop_error).
+    ///
+    /// # Arguments
+    ///
+    /// * `drop_policy` - what to do with pending changes on drop
+    ///
+    pub fn set_drop_policy(&mut self, drop_policy: DropPolicy) {
+        self.drop_policy = drop_policy;
+    }
+
+    /// Set a callback that receives the error when dumping the DB on drop fails. Without a callback the
+    /// error is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - the function to call with the error
+    ///
+    /// # Examples
+    ///
+    /// db.on_drop_error(|err| eprintln!("failed to save the DB: {}", err));
+    ///
+    pub fn on_drop_error<F>(&mut self, callback: F)
+    where
+        F: FnMut(Error) + Send + Sync + 'static,
+    {
+        self.drop_error_callback = Some(Box::new(callback));
+    }
+
+    /// Set how hard writing the DB tries to make sure the data is on disk. The default is
+    /// [Durability::NoSync](enum.Durability.html#variant.NoSync).
+    ///
+    /// Whatever the setting, the DB file is replaced by writing a new file next to it and renaming it into
+    /// place, so a crash in the middle of a dump leaves the previous DB file intact.
+    ///
+    /// # Arguments
+    ///
+    /// * `durability` - when to flush written data to disk
+    ///
+    pub fn set_durability(&mut self, durability: Durability) {
+        self.durability = durability;
+    }
+
+    fn dumpdb(&mut self, keys: &[&str]) -> Result<()> {
+        match self.staging.as_mut() {
+            Some(staging) => {
+                staging.stage(keys);
+                Ok(())
+            }
+            None => self.dump_changes(keys, 1),
+        }
+    }
+
+    fn dump_changes(&mut self, keys: &[&str], changes: usize) -> Result<()> {
+        self.pending_changes += changes;
+        let dumped = match self.dump_policy {
+            DumpPolicy::Auto => self.dump(),
+            // every change in the log is as good as dumped
+            DumpPolicy::AppendLog => self.append_log(keys).map(|_| self.pending_changes = 0),
+            DumpPolicy::Periodic(duration) => {
+                let now = Instant::now();
+                if now.duration_since(self.last_dump) > duration {
+                    self.last_dump = Instant::now();
+                    self.dump()
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Ok(()),
+        };
+
+        // the caller undoes the change when dumping it fails
+        if dumped.is_err() {
+            self.pending_changes -= changes;
+        }
+        dumped
+    }
+
+    /// Stage changes instead of dumping them, until [Pickle::resume_dumps()](#method.resume_dumps).
+    /// Returns false if changes are staged already, by a transaction or a batch this one is part of.
+    pub(crate) fn suspend_dumps(&mut self) -> bool {
+        match self.staging {
+            Some(_) => false,
+            None => {
+                self.staging = Some(Staging::default());
+                true
+            }
+        }
+    }
+
+    /// Dump the changes staged since [Pickle::suspend_dumps()](#method.suspend_dumps).
+    pub(crate) fn resume_dumps(&mut self) -> Result<()> {
+        let staging = self.staging.take().unwrap_or_default();
+        let changes = staging.changes;
+        let dumped = self.commit_staged(&staging);
+        // unlike a transaction, a batch keeps its changes when they can't be dumped
+        if dumped.is_err() {
+            self.pending_changes += changes;
+        }
+        dumped
+    }
+
+    fn commit_staged(&mut self, staging: &Staging) -> Result<()> {
+        if staging.dump_requested {
+            self.pending_changes += staging.changes;
+            let dumped = self.dump();
+            if dumped.is_err() {
+                self.pending_changes -= staging.changes;
+            }
+            return dumped;
+        }
+
+        match staging.keys.is_empty() {
+            true => Ok(()),
+            false => {
+                let keys: Vec<&str> = staging.keys.iter().map(String::as_str).collect();
+                self.dump_changes(&keys, staging.changes)
+            }
+        }
+    }
+
+    // Put the keys changed in a transaction back the way they were before it.
+    fn roll_back(&mut self, staging: Staging) {
+        for (key, original) in staging.originals.unwrap_or_default() {
+            self.take_entry(&key);
+            if let Some(original) = original {
+                self.put_entry(&key, original);
+            }
+        }
+    }
+
+    // Get a key ready to be changed: an expired key is dropped first, and in a transaction whatever the
+    // key holds is saved the first time it changes, so the transaction can be rolled back.
+    fn begin_change(&mut self, key: &str) {
+        self.purge_if_expired(key);
+        let saved = match self
+            .staging
+            .as_ref()
+            .and_then(|staging| staging.originals.as_ref())
+        {
+            Some(originals) => originals.contains_key(key),
+            None => true,
+        };
+        if !saved {
+            let original = self.clone_entry(key);
+            if let Some(originals) = self
+                .staging
+                .as_mut()
+                .and_then(|staging| staging.originals.as_mut())
+            {
+                originals.insert(String::from(key), original);
+            }
+        }
+    }
+
+    fn is_expired(&self, key: &str) -> bool {
+        match self.expiry_map.get(key) {
+            Some(expires_at) => *expires_at <= now_millis(),
+            None => false,
+        }
+    }
+
+    // An expired key is as good as gone, in memory and in the DB file, so it's dropped without a dump.
+    fn purge_if_expired(&mut self, key: &str) {
+        if self.is_expired(key) {
+            self.map.remove(key);
+            self.list_map.remove(key);
+            self.dict_map.remove(key);
+            self.expiry_map.remove(key);
+        }
+    }
+
+    fn purge_expired(&mut self) {
+        let expired: Vec<String> = self
+            .expiry_map
+            .keys()
+            .filter(|key| self.is_expired(key))
+            .cloned()
+            .collect();
+        for key in expired {
+            self.purge_if_expired(&key);
+        }
+    }
+
+    fn restore_expiry(&mut self, key: &str, expiry: Option<u64>) {
+        match expiry {
+            Some(expires_at) => self.expiry_map.insert(String::from(key), expires_at),
+            None => self.expiry_map.remove(key),
+        };
+    }
+
+    /// Dump the DB if it has changes that aren't in the DB file yet.
+    pub(crate) fn dump_pending(&mut self) -> Result<()> {
+        match self.pending_changes {
+            0 => Ok(()),
+            _ => self.dump(),
+        }
+    }
+
+    pub(crate) fn dump_policy(&self) -> &DumpPolicy {
+        &self.dump_policy
+    }
+
+    /// Replace a dump policy that doesn't involve a log file with another one that doesn't either.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn set_dump_policy(&mut self, dump_policy: DumpPolicy) {
+        self.dump_policy = dump_policy;
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        match self.lock {
+            Some((_, lock_mode)) if lock_mode.is_shared() => {
+                Err(Error::new(ErrorCode::Locked(format!(
+                    "DB '{}' is opened with a shared lock and can't be written",
+                    self.db_file_path.display()
+                ))))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Append a record with the current state of `keys` to the log. The first change after creating a new DB
+    // writes a full DB file instead, so the log always has a DB file to be replayed over.
+    fn append_log(&mut self, keys: &[&str]) -> Result<()> {
+        self.check_writable()?;
+        let log_file = match self.log_file.as_mut() {
+            Some(log_file) => log_file,
+            None => return self.dump(),
+        };
+
+        let record = match append_log::encode_record(
+            &self.serializer,
+            keys,
+            &self.map,
+            &self.list_map,
+            &self.dict_map,
+            &self.expiry_map,
+        ) {
+            Ok(record) => record,
+            Err(err_str) => return Err(Error::new(ErrorCode::Serialization(err_str))),
+        };
+
+        let log_len = match log_file.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+        };
+        let written = match self.durability {
+            Durability::NoSync => log_file.write_all(&record),
+            _ => log_file
+                .write_all(&record)
+                .and_then(|_| log_file.sync_data()),
+        };
+        match written {
+            Ok(_) => (),
+            Err(err) => {
+                // don't leave a partial record behind for the next one to be appended to
+                let _ = log_file.set_len(log_len);
+                return Err(Error::new(ErrorCode::Io(err)));
+            }
+        }
+
+        // the change is already in the log, so a failed compaction is left to be retried on the next one
+        let log_len = log_len + record.len() as u64;
+        if self
+            .compaction_threshold
+            .is_reached(log_len, self.snapshot_len)
+        {
+            let _ = self.compact();
+        }
+        Ok(())
+    }
+
+    /// Check if a key exists.
+    ///
+    /// This method returns `true` if the key exists and `false` otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key to check
+    ///
+    pub fn exists(&self, key: &str) -> bool {
+        if self.is_expired(key) {
+            return false;
+        }
+        self.map.contains_key(key)
+            || self.list_map.contains_key(key)
+            || self.dict_map.contains_key(key)
+    }
+
+    /// Return the count of keys in the database
+    ///
+    /// This method returns a `usize` of the number of keys in the database
+    ///
+    /// # Examples
+    ///
+    /// let count = db.key_count();
+    ///
+    pub fn key_count(&self) -> usize {
+        let expired = self.expiry_map.keys().filter(|key| self.is_expired(key));
+        self.map.iter().len() + self.list_map.iter().len() + self.dict_map.iter().len()
+            - expired.count()
+    }
+
+    /// Return a vector of keys in the database
+    ///
+    /// The keys of lists and dicts are included, expired keys aren't.
+    ///
+    /// # Examples
+    ///
+    /// let key_array = db.list_keys()
+    ///
+    pub fn list_keys(&self) -> Vec<String> {
+        let mut key_array: Vec<String> = Vec::new();
+        let keys = self
+            .map
+            .keys()
+            .chain(self.list_map.keys())
+            .chain(self.dict_map.keys());
+        for k in keys.filter(|k| !self.is_expired(k)) {
+            key_array.push(k.to_string());
+        }
+        key_array
+    }
+
+    /// Remove a key-value pair, a list or a dict from the DB.
+    ///
+    /// This methods returns `Ok(true)` if the key was found in the DB or `Ok(false)` if it wasn't found.
+    /// # Arguments
+    ///
+    /// * `key` the key, list name or dict name to remove
+    ///
+    pub fn remove(&mut self, key: &str) -> Result<bool> {
+        self.begin_change(key);
+        let original_expiry = self.expiry_map.remove(key);
+        let remove_map = match self.map.remove(key) {
+            None => None,
+            Some(val) => match self.dumpdb(&[key]) {
+                Ok(_) => Some(val),
+                Err(err) => {
+                    self.map.insert(String::from(key), val);
+                    self.restore_expiry(key, original_expiry);
+                    return Err(err);
+                }
+            },
+        };
+
+        let remove_list = match self.list_map.remove(key) {
+            None => None,
+            Some(list) => match self.dumpdb(&[key]) {
+                Ok(_) => Some(list),
+                Err(err) => {
+                    self.list_map.insert(String::from(key), list);
+                    self.restore_expiry(key, original_expiry);
+                    return Err(err);
+                }
+            },
+        };
+
+        let remove_dict = match self.dict_map.remove(key) {
+            None => None,
+            Some(dict) => match self.dumpdb(&[key]) {
+                Ok(_) => Some(dict),
+                Err(err) => {
+                    self.dict_map.insert(String::from(key), dict);
+                    self.restore_expiry(key, original_expiry);
+                    return Err(err);
+                }
+            },
+        };
+
+        Ok(remove_map.is_some() || remove_list.is_some() || remove_dict.is_some())
+    }
+
+    /// Rename a key, along with its value, list or dict and its expiry time.
+    ///
+    /// If `new` already exists and `overwrite` is `false`, an error of type
+    /// [KeyExists](error/enum.ErrorType.html#variant.KeyExists) is returned; otherwise whatever `new` held
+    /// is replaced. If `old` doesn't exist an error of type
+    /// [NotFound](error/enum.ErrorType.html#variant.NotFound) is returned. Both keys are dumped in a single
+    /// change, and if dumping the DB fails both are restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - the key to rename
+    /// * `new` - the new name of the key
+    /// * `overwrite` - whether to replace `new` if it exists
+    ///
+    /// # Examples
+    ///
+    /// // rename a list, unless the new name is taken
+    /// db.rename("list1", "list2", false).unwrap();
+    ///
+    pub fn rename(&mut self, old: &str, new: &str, overwrite: bool) -> Result<()> {
+        self.check_move(old, new, overwrite)?;
+        if old == new {
+            return Ok(());
+        }
+
+        self.begin_change(old);
+        self.begin_change(new);
+        let original_entry = self.take_entry(new);
+        let entry = self.take_entry(old).unwrap();
+        self.put_entry(new, entry);
+        match self.dumpdb(&[old, new]) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let entry = self.take_entry(new).unwrap();
+                self.put_entry(old, entry);
+                if let Some(original_entry) = original_entry {
+                    self.put_entry(new, original_entry);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Copy a key, along with its value, list or dict and its expiry time, to another key.
+    ///
+    /// If `dst` already exists and `overwrite` is `false`, an error of type
+    /// [KeyExists](error/enum.ErrorType.html#variant.KeyExists) is returned; otherwise whatever `dst` held
+    /// is replaced. If `src` doesn't exist an error of type
+    /// [NotFound](error/enum.ErrorType.html#variant.NotFound) is returned. If dumping the DB fails, `dst` is
+    /// restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - the key to copy
+    /// * `dst` - the key to copy to
+    /// * `overwrite` - whether to replace `dst` if it exists
+    ///
+    pub fn copy(&mut self, src: &str, dst: &str, overwrite: bool) -> Result<()> {
+        self.check_move(src, dst, overwrite)?;
+        if src == dst {
+            return Ok(());
+        }
+
+        self.begin_change(dst);
+        let entry = self.clone_entry(src).unwrap();
+        let original_entry = self.take_entry(dst);
+        self.put_entry(dst, entry);
+        match self.dumpdb(&[dst]) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.take_entry(dst);
+                if let Some(original_entry) = original_entry {
+                    self.put_entry(dst, original_entry);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn check_move(&mut self, from: &str, to: &str, overwrite: bool) -> Result<()> {
+        self.purge_if_expired(from);
+        self.purge_if_expired(to);
+        if !self.exists(from) {
+            return Err(Error::new(ErrorCode::NotFound(format!(
+                "key '{}' doesn't exist",
+                from
+            ))));
+        }
+        if !overwrite && from != to && self.exists(to) {
+            return Err(Error::new(ErrorCode::KeyExists(format!(
+                "key '{}' already exists",
+                to
+            ))));
+        }
+        Ok(())
+    }
+
+    fn clone_entry(&self, key: &str) -> Option<KeyEntry> {
+        let data = if let Some(value) = self.map.get(key) {
+            KeyData::Value(value.clone())
+        } else if let Some(list) = self.list_map.get(key) {
+            KeyData::List(list.clone())
+        } else {
+            KeyData::Dict(self.dict_map.get(key)?.clone())
+        };
+        Some(KeyEntry {
+            data,
+            expires_at: self.expiry_map.get(key).copied(),
+        })
+    }
+
+    fn take_entry(&mut self, key: &str) -> Option<KeyEntry> {
+        let expires_at = self.expiry_map.remove(key);
+        let data = if let Some(value) = self.map.remove(key) {
+            KeyData::Value(value)
+        } else if let Some(list) = self.list_map.remove(key) {
+            KeyData::List(list)
+        } else {
+            KeyData::Dict(self.dict_map.remove(key)?)
+        };
+        Some(KeyEntry { data, expires_at })
+    }
+
+    fn put_entry(&mut self, key: &str, entry: KeyEntry) {
+        match entry.data {
+            KeyData::Value(value) => {
+                self.map.insert(String::from(key), value);
+            }
+            KeyData::List(list) => {
+                self.list_map.insert(String::from(key), list);
+            }
+            KeyData::Dict(dict) => {
+                self.dict_map.insert(String::from(key), dict);
+            }
+        }
+        self.restore_expiry(key, entry.expires_at);
+    }
+
+    /// Create a new list.
+    ///
+    /// This method just creates a new list, it doesn't add any elements to it. If another list, dict or value
+    /// is already set under this key, it will be overridden by the new, empty list.
+    /// If dumping the DB fails, the previous list, dict or value is restored and the error is returned.
+    ///
+    /// The method returns a [PickleListExtender](struct.PickleListExtender.html) that can be used to add
+    /// items to the newly created list.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the key of the list that will be created
+    ///
+    /// # Examples
+    ///
+    /// // create a new list and add a few items to it
+    /// db.lcreate("list1").unwrap().ladd(&100).ladd(&String::from("hello"));
+    ///
+    pub fn lcreate(&mut self, name: &str) -> Result<PickleListExtender<'_>> {
+        self.begin_change(name);
+        let original_expiry = self.expiry_map.remove(name);
+        let original_value = self.map.remove(name);
+        let original_dict = self.dict_map.remove(name);
+        let original_list = self.list_map.insert(String::from(name), Vec::new());
+
+        match self.dumpdb(&[name]) {
+            Ok(_) => Ok(PickleListExtender {
+                db: self,
+                list_name: String::from(name),
+            }),
+            Err(err) => {
+                match original_list {
+                    None => {
+                        self.list_map.remove(name);
+                    }
+                    Some(orig_list) => {
+                        self.list_map.insert(String::from(name), orig_list);
+                    }
+                }
+                if let Some(orig_value) = original_value {
+                    self.map.insert(String::from(name), orig_value);
+                }
+                if let Some(orig_dict) = original_dict {
+                    self.dict_map.insert(String::from(name), orig_dict);
+                }
+                self.restore_expiry(name, original_expiry);
+
+                Err(err)
+            }
+        }
+    }
+
+    /// Check if a list contains a certain value.
+    ///
+    /// The value is serialized and compared against the serialized items of the list, so the value type
+    /// doesn't need to be given explicitly. Returns `false` if the list doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `value` - the value to look for
+    ///
+    pub fn lexists<V>(&self, name: &str, value: &V) -> bool
+    where
+        V: Serialize,
+    {
+        if self.is_expired(name) {
+            return false;
+        }
+        let ser_data = match self.serializer.serialize_data(value) {
+            Ok(data) => data,
+            Err(_) => return false,
+        };
+
+        match self.list_map.get(name) {
+            Some(list) => list.contains(&ser_data),
+            None => false,
+        }
+    }
+
+    /// Get an item of a certain list in a certain position.
+    ///
+    /// It's the user's responsibility to know the value type and give it while calling this method.
+    /// If the list doesn't exist, the position is out of bounds or the type is wrong, `None` will be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `pos` - the position of the item inside the list
+    ///
+    /// # Examples
+    ///
+    /// // read the first item of a list
+    /// let item = db.lget::<i32>("list1", 0).unwrap();
+    ///
+    pub fn lget<V>(&self, name: &str, pos: usize) -> Option<V>
+    where
+        V: DeserializeOwned,
+    {
+        if self.is_expired(name) {
+            return None;
+        }
+        match self.list_map.get(name) {
+            Some(list) => match list.get(pos) {
+                Some(item) => self.serializer.deserialize_data::<V>(item),
+                None => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Get all the items of a certain list.
+    ///
+    /// All items are deserialized into the same type `V`. If the list doesn't exist or any of its items
+    /// can't be deserialized into `V`, `None` will be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    ///
+    pub fn lgetall<V>(&self, name: &str) -> Option<Vec<V>>
+    where
+        V: DeserializeOwned,
+    {
+        if self.is_expired(name) {
+            return None;
+        }
+        match self.list_map.get(name) {
+            Some(list) => list
+                .iter()
+                .map(|item| self.serializer.deserialize_data::<V>(item))
+                .collect(),
+            None => None,
+        }
+    }
+
+    /// Get the length of a list.
+    ///
+    /// If the list doesn't exist, 0 will be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    ///
+    pub fn llen(&self, name: &str) -> usize {
+        if self.is_expired(name) {
+            return 0;
+        }
+        match self.list_map.get(name) {
+            Some(list) => list.len(),
+            None => 0,
+        }
+    }
+
+    pub fn ladd<V>(&mut self, name: &str, value: &V) -> Option<PickleListExtender<'_>>
+    where
+        V: Serialize,
+    {
+        self.lextend(name, &[value])
+    }
+
+    pub fn lextend<'a, V, I>(&mut self, name: &str, seq: I) -> Option<PickleListExtender<'_>>
+    where
+        V: 'a + Serialize,
+        I: IntoIterator<Item = &'a V>,
+    {
+        self.begin_change(name);
+        let serializer = &self.serializer;
+        match self.list_map.get_mut(name) {
+            Some(list) => {
                 let original_len = list.len();
                 let serialized: Vec<Vec<u8>> = seq
                     .into_iter()
                     .map(|x| serializer.serialize_data(x).unwrap())
                     .collect();
                 list.extend(serialized);
-                match self.dumpdb() {
+                match self.dumpdb(&[name]) {
                     Ok(_) => (),
                     Err(_) => {
                         let same_list = self.list_map.get_mut(name).unwrap();
@@ -308,13 +1853,868 @@ impl Pickle {
                         return None;
                     }
                 }
-                Some(PickleListExtender {
-                    db: self,
-                    list_name: String::from(name),
-                })
+                Some(PickleListExtender {
+                    db: self,
+                    list_name: String::from(name),
+                })
+            }
+
+            None => None,
+        }
+    }
+
+    /// Pop an item out of a list.
+    ///
+    /// The item in position `pos` is removed from the list and returned. A negative `pos` counts from the
+    /// end of the list, so `-1` pops the last item. It's the user's responsibility to know the item type and
+    /// give it while calling this method; if the item can't be deserialized into `V` the list isn't changed.
+    /// If dumping the DB fails, the item is put back in its place and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `pos` - the position of the item to pop
+    ///
+    /// # Examples
+    ///
+    /// // pop the last item of a list
+    /// let item = db.lpop::<i32>("list1", -1).unwrap();
+    ///
+    pub fn lpop<V>(&mut self, name: &str, pos: isize) -> Result<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.begin_change(name);
+        let index = self.list_index(name, pos, false)?;
+        let value = match self
+            .serializer
+            .deserialize_data::<V>(&self.list_map[name][index])
+        {
+            Some(val) => val,
+            None => {
+                return Err(Error::new(ErrorCode::Serialization(format!(
+                    "item {} of list '{}' can't be deserialized into the requested type",
+                    pos, name
+                ))))
+            }
+        };
+
+        self.lpop_raw(name, index)?;
+        Ok(value)
+    }
+
+    /// Remove a list and return its items.
+    ///
+    /// All items are deserialized into the same type `V`; if any of them can't be deserialized the list
+    /// isn't removed. If dumping the DB fails, the list is restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    ///
+    pub fn lremlist<V>(&mut self, name: &str) -> Result<Vec<V>>
+    where
+        V: DeserializeOwned,
+    {
+        self.begin_change(name);
+        let values = match self.lgetall::<V>(name) {
+            Some(values) => values,
+            None if self.list_map.contains_key(name) => {
+                return Err(Error::new(ErrorCode::Serialization(format!(
+                    "items of list '{}' can't be deserialized into the requested type",
+                    name
+                ))))
+            }
+            None => return Err(list_not_found(name)),
+        };
+
+        let list = self.list_map.remove(name).unwrap();
+        let original_expiry = self.expiry_map.remove(name);
+        match self.dumpdb(&[name]) {
+            Ok(_) => Ok(values),
+            Err(err) => {
+                self.list_map.insert(String::from(name), list);
+                self.restore_expiry(name, original_expiry);
+                Err(err)
+            }
+        }
+    }
+
+    /// Remove the first occurrence of a value from a list.
+    ///
+    /// The value is serialized and compared against the serialized items of the list.
+    /// This method returns `Ok(true)` if the value was found and removed or `Ok(false)` if it wasn't found.
+    /// If dumping the DB fails, the value is put back in its place and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `value` - the value to remove
+    ///
+    pub fn lremvalue<V>(&mut self, name: &str, value: &V) -> Result<bool>
+    where
+        V: Serialize,
+    {
+        self.begin_change(name);
+        let ser_data = match self.serializer.serialize_data(value) {
+            Ok(data) => data,
+            Err(err_str) => return Err(Error::new(ErrorCode::Serialization(err_str))),
+        };
+
+        let index = match self.list_map.get(name) {
+            Some(list) => match list.iter().position(|item| *item == ser_data) {
+                Some(index) => index,
+                None => return Ok(false),
+            },
+            None => return Err(list_not_found(name)),
+        };
+
+        self.lpop_raw(name, index)?;
+        Ok(true)
+    }
+
+    /// Insert a value into a list in a certain position.
+    ///
+    /// All items from position `pos` onwards are shifted to the right. A negative `pos` counts from the end
+    /// of the list, and a `pos` equal to the list length appends the value.
+    /// If dumping the DB fails, the value is removed again and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `pos` - the position to insert the value at
+    /// * `value` - the value to insert
+    ///
+    pub fn linsert<V>(
+        &mut self,
+        name: &str,
+        pos: isize,
+        value: &V,
+    ) -> Result<PickleListExtender<'_>>
+    where
+        V: Serialize,
+    {
+        self.begin_change(name);
+        let index = self.list_index(name, pos, true)?;
+        let ser_data = match self.serializer.serialize_data(value) {
+            Ok(data) => data,
+            Err(err_str) => return Err(Error::new(ErrorCode::Serialization(err_str))),
+        };
+
+        self.list_map.get_mut(name).unwrap().insert(index, ser_data);
+        match self.dumpdb(&[name]) {
+            Ok(_) => Ok(PickleListExtender {
+                db: self,
+                list_name: String::from(name),
+            }),
+            Err(err) => {
+                self.list_map.get_mut(name).unwrap().remove(index);
+                Err(err)
+            }
+        }
+    }
+
+    /// Overwrite the item of a list in a certain position.
+    ///
+    /// A negative `pos` counts from the end of the list.
+    /// If dumping the DB fails, the original item is restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the list key
+    /// * `pos` - the position of the item to overwrite
+    /// * `value` - the new value
+    ///
+    pub fn lset<V>(&mut self, name: &str, pos: isize, value: &V) -> Result<PickleListExtender<'_>>
+    where
+        V: Serialize,
+    {
+        self.begin_change(name);
+        let index = self.list_index(name, pos, false)?;
+        let ser_data = match self.serializer.serialize_data(value) {
+            Ok(data) => data,
+            Err(err_str) => return Err(Error::new(ErrorCode::Serialization(err_str))),
+        };
+
+        let original_value =
+            std::mem::replace(&mut self.list_map.get_mut(name).unwrap()[index], ser_data);
+        match self.dumpdb(&[name]) {
+            Ok(_) => Ok(PickleListExtender {
+                db: self,
+                list_name: String::from(name),
+            }),
+            Err(err) => {
+                self.list_map.get_mut(name).unwrap()[index] = original_value;
+                Err(err)
+            }
+        }
+    }
+
+    /// Create a new dict.
+    ///
+    /// This method just creates a new dict, it doesn't add any pairs to it. If another dict, list or value
+    /// is already set under this key, it will be overridden by the new, empty dict.
+    /// If dumping the DB fails, the previous dict, list or value is restored and the error is returned.
+    ///
+    /// The method returns a [PickleDictExtender](struct.PickleDictExtender.html) that can be used to add
+    /// pairs to the newly created dict.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the key of the dict that will be created
+    ///
+    /// # Examples
+    ///
+    /// // create a new dict and add a few pairs to it
+    /// db.dcreate("dict1").unwrap().dadd("a", &1).unwrap().dadd("b", &2).unwrap();
+    ///
+    pub fn dcreate(&mut self, name: &str) -> Result<PickleDictExtender<'_>> {
+        self.begin_change(name);
+        let original_expiry = self.expiry_map.remove(name);
+        let original_value = self.map.remove(name);
+        let original_list = self.list_map.remove(name);
+        let original_dict = self.dict_map.insert(String::from(name), HashMap::new());
+
+        match self.dumpdb(&[name]) {
+            Ok(_) => Ok(PickleDictExtender {
+                db: self,
+                dict_name: String::from(name),
+            }),
+            Err(err) => {
+                match original_dict {
+                    None => {
+                        self.dict_map.remove(name);
+                    }
+                    Some(orig_dict) => {
+                        self.dict_map.insert(String::from(name), orig_dict);
+                    }
+                }
+                if let Some(orig_value) = original_value {
+                    self.map.insert(String::from(name), orig_value);
+                }
+                if let Some(orig_list) = original_list {
+                    self.list_map.insert(String::from(name), orig_list);
+                }
+                self.restore_expiry(name, original_expiry);
+
+                Err(err)
+            }
+        }
+    }
+
+    /// Add a key-value pair to a dict.
+    ///
+    /// If the key already exists in the dict, its value is overwritten.
+    /// If dumping the DB fails, the previous value of the key is restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the dict key
+    /// * `key` - the key of the pair inside the dict
+    /// * `value` - the value of the pair
+    ///
+    pub fn dadd<V>(&mut self, name: &str, key: &str, value: &V) -> Result<PickleDictExtender<'_>>
+    where
+        V: Serialize,
+    {
+        self.begin_change(name);
+        if !self.dict_map.contains_key(name) {
+            return Err(dict_not_found(name));
+        }
+        let ser_data = match self.serializer.serialize_data(value) {
+            Ok(data) => data,
+            Err(err_str) => return Err(Error::new(ErrorCode::Serialization(err_str))),
+        };
+
+        let dict = self.dict_map.get_mut(name).unwrap();
+        let original_value = dict.insert(String::from(key), ser_data);
+        match self.dumpdb(&[name]) {
+            Ok(_) => Ok(PickleDictExtender {
+                db: self,
+                dict_name: String::from(name),
+            }),
+            Err(err) => {
+                let dict = self.dict_map.get_mut(name).unwrap();
+                match original_value {
+                    None => {
+                        dict.remove(key);
+                    }
+                    Some(orig_value) => {
+                        dict.insert(String::from(key), orig_value);
+                    }
+                }
+
+                Err(err)
             }
+        }
+    }
+
+    /// Get the value of a key inside a dict.
+    ///
+    /// It's the user's responsibility to know the value type and give it while calling this method.
+    /// If the dict or the key doesn't exist or if the type is wrong, `None` will be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the dict key
+    /// * `key` - the key of the pair inside the dict
+    ///
+    /// # Examples
+    ///
+    /// let num = db.dget::<i32>("dict1", "a").unwrap();
+    ///
+    pub fn dget<V>(&self, name: &str, key: &str) -> Option<V>
+    where
+        V: DeserializeOwned,
+    {
+        if self.is_expired(name) {
+            return None;
+        }
+        match self.dict_map.get(name) {
+            Some(dict) => match dict.get(key) {
+                Some(item) => self.serializer.deserialize_data::<V>(item),
+                None => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Get all the pairs of a dict.
+    ///
+    /// All values are deserialized into the same type `V`. If the dict doesn't exist or any of its values
+    /// can't be deserialized into `V`, `None` will be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the dict key
+    ///
+    pub fn dgetall<V>(&self, name: &str) -> Option<HashMap<String, V>>
+    where
+        V: DeserializeOwned,
+    {
+        if self.is_expired(name) {
+            return None;
+        }
+        match self.dict_map.get(name) {
+            Some(dict) => dict
+                .iter()
+                .map(|(key, item)| {
+                    self.serializer
+                        .deserialize_data::<V>(item)
+                        .map(|val| (key.to_string(), val))
+                })
+                .collect(),
+            None => None,
+        }
+    }
+
+    /// Get the keys of a dict.
+    ///
+    /// If the dict doesn't exist, `None` will be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the dict key
+    ///
+    pub fn dkeys(&self, name: &str) -> Option<Vec<String>> {
+        if self.is_expired(name) {
+            return None;
+        }
+        self.dict_map
+            .get(name)
+            .map(|dict| dict.keys().map(|key| key.to_string()).collect())
+    }
 
+    /// Get the values of a dict.
+    ///
+    /// All values are deserialized into the same type `V`. If the dict doesn't exist or any of its values
+    /// can't be deserialized into `V`, `None` will be returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the dict key
+    ///
+    pub fn dvals<V>(&self, name: &str) -> Option<Vec<V>>
+    where
+        V: DeserializeOwned,
+    {
+        if self.is_expired(name) {
+            return None;
+        }
+        match self.dict_map.get(name) {
+            Some(dict) => dict
+                .values()
+                .map(|item| self.serializer.deserialize_data::<V>(item))
+                .collect(),
             None => None,
         }
     }
+
+    /// Check if a key exists inside a dict.
+    ///
+    /// Returns `false` if the dict doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the dict key
+    /// * `key` - the key to check
+    ///
+    pub fn dexists(&self, name: &str, key: &str) -> bool {
+        if self.is_expired(name) {
+            return false;
+        }
+        match self.dict_map.get(name) {
+            Some(dict) => dict.contains_key(key),
+            None => false,
+        }
+    }
+
+    /// Remove a dict and all of its pairs.
+    ///
+    /// This methods returns `Ok(true)` if the dict was found in the DB or `Ok(false)` if it wasn't found.
+    /// If dumping the DB fails, the dict is restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the dict key
+    ///
+    pub fn drem(&mut self, name: &str) -> Result<bool> {
+        self.begin_change(name);
+        match self.dict_map.remove(name) {
+            None => Ok(false),
+            Some(dict) => {
+                let original_expiry = self.expiry_map.remove(name);
+                match self.dumpdb(&[name]) {
+                    Ok(_) => Ok(true),
+                    Err(err) => {
+                        self.dict_map.insert(String::from(name), dict);
+                        self.restore_expiry(name, original_expiry);
+                        Err(err)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pop a key out of a dict and return its value.
+    ///
+    /// It's the user's responsibility to know the value type and give it while calling this method; if the
+    /// value can't be deserialized into `V` the dict isn't changed.
+    /// If dumping the DB fails, the pair is restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the dict key
+    /// * `key` - the key of the pair to pop
+    ///
+    pub fn dpop<V>(&mut self, name: &str, key: &str) -> Result<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.begin_change(name);
+        let item = match self.dict_map.get(name) {
+            Some(dict) => match dict.get(key) {
+                Some(item) => item,
+                None => {
+                    return Err(Error::new(ErrorCode::NotFound(format!(
+                        "key '{}' not found in dict '{}'",
+                        key, name
+                    ))))
+                }
+            },
+            None => return Err(dict_not_found(name)),
+        };
+        let value = match self.serializer.deserialize_data::<V>(item) {
+            Some(val) => val,
+            None => {
+                return Err(Error::new(ErrorCode::Serialization(format!(
+                    "key '{}' of dict '{}' can't be deserialized into the requested type",
+                    key, name
+                ))))
+            }
+        };
+
+        let item = self.dict_map.get_mut(name).unwrap().remove(key).unwrap();
+        match self.dumpdb(&[name]) {
+            Ok(_) => Ok(value),
+            Err(err) => {
+                self.dict_map
+                    .get_mut(name)
+                    .unwrap()
+                    .insert(String::from(key), item);
+                Err(err)
+            }
+        }
+    }
+
+    /// Merge the pairs of one dict into another.
+    ///
+    /// All pairs of `name2` are copied into `name1`, overwriting values of keys that exist in both.
+    /// `name2` isn't changed. The method returns the number of pairs in `name1` after the merge.
+    /// If dumping the DB fails, `name1` is restored and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name1` - the key of the dict to merge into
+    /// * `name2` - the key of the dict to merge from
+    ///
+    pub fn dmerge(&mut self, name1: &str, name2: &str) -> Result<usize> {
+        self.begin_change(name1);
+        self.purge_if_expired(name2);
+        let other = match self.dict_map.get(name2) {
+            Some(dict) => dict.clone(),
+            None => return Err(dict_not_found(name2)),
+        };
+        let dict = match self.dict_map.get_mut(name1) {
+            Some(dict) => dict,
+            None => return Err(dict_not_found(name1)),
+        };
+
+        let original_dict = dict.clone();
+        dict.extend(other);
+        let merged_len = dict.len();
+        match self.dumpdb(&[name1]) {
+            Ok(_) => Ok(merged_len),
+            Err(err) => {
+                self.dict_map.insert(String::from(name1), original_dict);
+                Err(err)
+            }
+        }
+    }
+
+    /// Export the values and lists of the DB to a parquet file.
+    ///
+    /// The file holds a single table with one `(key, kind, index, value_json, value_bytes)` row per value
+    /// and per list item, so it can be queried by tools like DuckDB or pandas. `kind` is either `value` or
+    /// `list`, `index` is the position of a list item and null for values, and `value_json` is the value as
+    /// a json string. A serialization method that doesn't store the type of values, like
+    /// `SerializationMethod::Bin`, can't be converted to json, so its values are written to `value_bytes`
+    /// as they are stored and `value_json` is null. Dicts are not exported. Requires the `parquet` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the path of the parquet file to write, an existing file is overwritten
+    ///
+    #[cfg(feature = "parquet")]
+    pub fn export_parquet<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let self_describing = self.serializer.is_self_describing();
+        let to_value = |key: &str, ser_data: &[u8]| -> Result<ParquetValue> {
+            if !self_describing {
+                return Ok(ParquetValue::Bytes(ser_data.to_vec()));
+            }
+            match self
+                .serializer
+                .deserialize_data::<serde_json::Value>(ser_data)
+            {
+                Some(value) => Ok(ParquetValue::Json(value.to_string())),
+                None => Err(Error::new(ErrorCode::Serialization(format!(
+                    "the value of '{}' can't be converted to json",
+                    key
+                )))),
+            }
+        };
+
+        let mut rows = Vec::new();
+        for (key, value) in self.map.iter().filter(|(key, _)| !self.is_expired(key)) {
+            rows.push(ParquetRow {
+                key: key.to_string(),
+                kind: KIND_VALUE,
+                index: None,
+                value: to_value(key, value)?,
+            });
+        }
+        for (key, list) in self
+            .list_map
+            .iter()
+            .filter(|(key, _)| !self.is_expired(key))
+        {
+            for (index, item) in list.iter().enumerate() {
+                rows.push(ParquetRow {
+                    key: key.to_string(),
+                    kind: KIND_LIST,
+                    index: Some(index as i64),
+                    value: to_value(key, item)?,
+                });
+            }
+        }
+        rows.sort_by(|row1, row2| (&row1.key, row1.index).cmp(&(&row2.key, row2.index)));
+
+        parquet_io::write_rows(path, &rows)
+    }
+
+    /// Import values and lists from a parquet file written by [Pickle::export_parquet()](#method.export_parquet).
+    ///
+    /// Every key in the file replaces a value, list or dict with the same key in the DB, and all other
+    /// keys are left untouched. Lists are rebuilt in the order of their `index` column. Values written to
+    /// `value_bytes` are stored as they are, so they can only be imported into a DB with the serialization
+    /// method they were exported from. A DB that doesn't store the type of values can't import values
+    /// from `value_json`, and returns an error of type
+    /// [Serialization](error/enum.ErrorType.html#variant.Serialization) for them. Requires the `parquet`
+    /// feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - the path of the parquet file to read
+    ///
+    #[cfg(feature = "parquet")]
+    pub fn import_parquet<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let mut values: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut lists: HashMap<String, Vec<(i64, Vec<u8>)>> = HashMap::new();
+        for row in parquet_io::read_rows(path)? {
+            let ser_data = match row.value {
+                ParquetValue::Bytes(ser_data) => ser_data,
+                ParquetValue::Json(json) => self.json_to_value(&row.key, &json)?,
+            };
+
+            if row.kind == KIND_LIST {
+                let list = lists.entry(row.key).or_default();
+                list.push((row.index.unwrap_or(list.len() as i64), ser_data));
+            } else {
+                values.insert(row.key, ser_data);
+            }
+        }
+
+        let imported_keys: Vec<String> = values.keys().chain(lists.keys()).cloned().collect();
+        for key in imported_keys.iter() {
+            self.begin_change(key);
+        }
+        let original_map = self.map.clone();
+        let original_list_map = self.list_map.clone();
+        let original_dict_map = self.dict_map.clone();
+        let original_expiry_map = self.expiry_map.clone();
+        for key in imported_keys.iter() {
+            self.expiry_map.remove(key);
+        }
+        for (key, value) in values {
+            self.list_map.remove(&key);
+            self.dict_map.remove(&key);
+            self.map.insert(key, value);
+        }
+        for (key, mut list) in lists {
+            list.sort_by_key(|(index, _)| *index);
+            self.map.remove(&key);
+            self.dict_map.remove(&key);
+            self.list_map
+                .insert(key, list.into_iter().map(|(_, item)| item).collect());
+        }
+
+        let imported_keys: Vec<&str> = imported_keys.iter().map(String::as_str).collect();
+        match self.dumpdb(&imported_keys) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.map = original_map;
+                self.list_map = original_list_map;
+                self.dict_map = original_dict_map;
+                self.expiry_map = original_expiry_map;
+                Err(err)
+            }
+        }
+    }
+
+    #[cfg(feature = "parquet")]
+    fn json_to_value(&self, key: &str, json: &str) -> Result<Vec<u8>> {
+        if !self.serializer.is_self_describing() {
+            return Err(Error::new(ErrorCode::Serialization(format!(
+                "the json value of '{}' can't be imported into a DB that doesn't store the type of values",
+                key
+            ))));
+        }
+        match serde_json::from_str::<serde_json::Value>(json) {
+            Ok(value) => self.serialize_value(&value),
+            Err(err) => Err(Error::new(ErrorCode::Serialization(err.to_string()))),
+        }
+    }
+
+    /// Remove the item in position `index` of a list without deserializing it.
+    /// The index must already be resolved and within bounds.
+    pub(crate) fn lpop_raw(&mut self, name: &str, index: usize) -> Result<Vec<u8>> {
+        self.begin_change(name);
+        let item = self.list_map.get_mut(name).unwrap().remove(index);
+        match self.dumpdb(&[name]) {
+            Ok(_) => Ok(item),
+            Err(err) => {
+                self.list_map.get_mut(name).unwrap().insert(index, item);
+                Err(err)
+            }
+        }
+    }
+
+    /// Resolve a possibly negative list position into an index within the list bounds.
+    /// When `allow_end` is set, the position right after the last item is valid too.
+    pub(crate) fn list_index(&self, name: &str, pos: isize, allow_end: bool) -> Result<usize> {
+        let len = match self.list_map.get(name) {
+            Some(list) => list.len(),
+            None => return Err(list_not_found(name)),
+        };
+
+        let index = if pos < 0 {
+            len.checked_sub(pos.unsigned_abs())
+        } else {
+            Some(pos as usize)
+        };
+
+        match index {
+            Some(index) if index < len || (allow_end && index == len) => Ok(index),
+            _ => Err(Error::new(ErrorCode::OutOfRange(format!(
+                "position {} is out of range for list '{}' of length {}",
+                pos, name, len
+            )))),
+        }
+    }
+}
+
+impl Drop for Pickle {
+    fn drop(&mut self) {
+        if self.drop_policy == DropPolicy::Discard {
+            return;
+        }
+        if let Err(err) = self.dump_pending() {
+            if let Some(callback) = self.drop_error_callback.as_mut() {
+                callback(err);
+            }
+        }
+    }
+}
+
+fn wrong_type(key: &str, kind: &str) -> Error {
+    Error::new(ErrorCode::WrongType(format!(
+        "the value of '{}' isn't {}",
+        key, kind
+    )))
+}
+
+fn counter_overflow<V, D>(key: &str, value: V, delta: D) -> Error
+where
+    V: std::fmt::Display,
+    D: std::fmt::Display,
+{
+    Error::new(ErrorCode::Overflow(format!(
+        "adding {} to the value {} of '{}' overflows",
+        delta, value, key
+    )))
+}
+
+fn now_millis() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(now) => u64::try_from(now.as_millis()).unwrap_or(u64::MAX),
+        Err(_) => 0,
+    }
+}
+
+fn expiry_time(ttl: Duration) -> u64 {
+    now_millis().saturating_add(u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX))
+}
+
+// Tells apart the temp files of dumps that happen at the same time, in this process or in others.
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Write `data` to a temp file next to `file_path` and rename it over `file_path`, so readers only ever see
+// the old or the new file. The temp file is removed if anything goes wrong before the rename.
+fn write_file_atomically(file_path: &Path, data: &[u8], durability: Durability) -> Result<()> {
+    let mut temp_file_path = file_path.as_os_str().to_os_string();
+    temp_file_path.push(format!(
+        ".temp.{}.{}.{}",
+        process::id(),
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = fs::File::create(&temp_file_path).and_then(|mut temp_file| {
+        temp_file.write_all(data)?;
+        match durability {
+            Durability::NoSync => Ok(()),
+            _ => temp_file.sync_all(),
+        }
+    });
+    if let Err(err) = written.and_then(|_| fs::rename(&temp_file_path, file_path)) {
+        let _ = fs::remove_file(&temp_file_path);
+        return Err(Error::new(ErrorCode::Io(err)));
+    }
+
+    if durability == Durability::SyncFileAndDir {
+        sync_dir(file_path)?;
+    }
+    Ok(())
+}
+
+// Flush the directory entries of the directory holding `file_path`. Directories can't be opened as files
+// on windows, where the rename is flushed along with the file.
+#[cfg(unix)]
+fn sync_dir(file_path: &Path) -> Result<()> {
+    let dir_path = match file_path.parent() {
+        Some(dir_path) if !dir_path.as_os_str().is_empty() => dir_path,
+        _ => Path::new("."),
+    };
+    match fs::File::open(dir_path).and_then(|dir| dir.sync_all()) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::new(ErrorCode::Io(err))),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_file_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn log_file_path(db_file_path: &Path) -> PathBuf {
+    let mut log_file_path = db_file_path.as_os_str().to_os_string();
+    log_file_path.push(".log");
+    PathBuf::from(log_file_path)
+}
+
+// The DB file itself is replaced on every dump, so the lock is taken on a separate file that stays put.
+fn lock_db_file(db_file_path: &Path, lock_mode: LockMode) -> Result<fs::File> {
+    let mut lock_file_path = db_file_path.as_os_str().to_os_string();
+    lock_file_path.push(".lock");
+    let lock_file = match fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_file_path)
+    {
+        Ok(lock_file) => lock_file,
+        Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+    };
+
+    let locked = match lock_mode {
+        LockMode::Exclusive => lock_file.lock().map_err(fs::TryLockError::Error),
+        LockMode::Shared => lock_file.lock_shared().map_err(fs::TryLockError::Error),
+        LockMode::TryExclusive => lock_file.try_lock(),
+        LockMode::TryShared => lock_file.try_lock_shared(),
+    };
+    match locked {
+        Ok(_) => Ok(lock_file),
+        Err(fs::TryLockError::WouldBlock) => Err(Error::new(ErrorCode::Locked(format!(
+            "DB '{}' is locked by another process",
+            db_file_path.display()
+        )))),
+        Err(fs::TryLockError::Error(err)) => Err(Error::new(ErrorCode::Io(err))),
+    }
+}
+
+// Open the log for appending, dropping anything after `len` such as a record torn by a crash.
+fn open_log_file(log_file_path: &Path, len: u64) -> Result<fs::File> {
+    let log_file = match fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file_path)
+    {
+        Ok(log_file) => log_file,
+        Err(err) => return Err(Error::new(ErrorCode::Io(err))),
+    };
+    match log_file.set_len(len) {
+        Ok(_) => Ok(log_file),
+        Err(err) => Err(Error::new(ErrorCode::Io(err))),
+    }
+}
+
+fn list_not_found(name: &str) -> Error {
+    Error::new(ErrorCode::NotFound(format!("list '{}' not found", name)))
+}
+
+fn dict_not_found(name: &str) -> Error {
+    Error::new(ErrorCode::NotFound(format!("dict '{}' not found", name)))
 }
@@ -1,24 +1,182 @@
 use core::fmt;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-type DbMap = HashMap<String, Vec<u8>>;
-type DbListMap = HashMap<String, Vec<Vec<u8>>>;
+/// The key-value pairs of a DB, with values in their serialized form.
+pub type DbMap = HashMap<String, Vec<u8>>;
+/// The lists of a DB, with items in their serialized form.
+pub type DbListMap = HashMap<String, Vec<Vec<u8>>>;
+/// The dicts of a DB, with values in their serialized form.
+pub type DbDictMap = HashMap<String, HashMap<String, Vec<u8>>>;
+/// When the keys of a DB that expire do so, in milliseconds since the unix epoch.
+pub type DbExpiryMap = HashMap<String, u64>;
 
-// Currently we will start with json serialization, because I unserstand it. Binary and maybe yaml coming later. maybe parquet
+/// The contents of a DB as read from a file by [PickleSerializer::deserialize_db()].
+///
+/// Values are kept in the form produced by [PickleSerializer::serialize_data()]. New kinds of data may be
+/// added in the future, so start from `DbContents::default()` and fill in the fields.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct DbContents {
+    pub map: DbMap,
+    pub list_map: DbListMap,
+    pub dict_map: DbDictMap,
+    pub expiry_map: DbExpiryMap,
+}
+
+/// The contents of a DB as handed to [PickleSerializer::serialize_db()] for writing to a file.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct DbContentsRef<'a> {
+    pub map: &'a DbMap,
+    pub list_map: &'a DbListMap,
+    pub dict_map: &'a DbDictMap,
+    pub expiry_map: &'a DbExpiryMap,
+}
+
+impl DbContents {
+    /// Borrow the contents, e.g. to pass them on to another serializer.
+    pub fn as_ref(&self) -> DbContentsRef<'_> {
+        DbContentsRef {
+            map: &self.map,
+            list_map: &self.list_map,
+            dict_map: &self.dict_map,
+            expiry_map: &self.expiry_map,
+        }
+    }
+}
+
+/// Receives the deserializer of a single value in [PickleSerializer::deserialize_data()].
+pub type DataSink<'s> = dyn for<'de> FnMut(&mut dyn erased_serde::Deserializer<'de>) -> Result<(), erased_serde::Error>
+    + 's;
+
+/// A format that `Pickle` can store its values and DB files in.
+///
+/// The built-in formats are selected through [SerializationMethod], and any other format can be plugged in
+/// with [Pickle::with_serializer()](struct.Pickle.html#method.with_serializer) by implementing this trait.
+/// Values go through [erased_serde] so the trait can be used as a `Box<dyn PickleSerializer>`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rusty_pickle::{erased_serde, DataSink, DbContents, DbContentsRef, DumpPolicy, JsonSerializer,
+///     Pickle, PickleSerializer};
+///
+/// // a serializer that reverses the bytes of the DB file and delegates everything else to json
+/// struct ReversedJson(JsonSerializer);
+///
+/// impl PickleSerializer for ReversedJson {
+///     fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String> {
+///         self.0.serialize_data(data)
+///     }
+///
+///     fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String> {
+///         self.0.deserialize_data(ser_data, sink)
+///     }
+///
+///     fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String> {
+///         let mut ser_db = self.0.serialize_db(db)?;
+///         ser_db.reverse();
+///         Ok(ser_db)
+///     }
+///
+///     fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+///         let mut ser_db = ser_db.to_vec();
+///         ser_db.reverse();
+///         self.0.deserialize_db(&ser_db)
+///     }
+/// }
+///
+/// let mut db = Pickle::with_serializer(
+///     "example.db",
+///     DumpPolicy::Auto,
+///     Box::new(ReversedJson(JsonSerializer::new())),
+/// );
+/// ```
+pub trait PickleSerializer: Send + Sync {
+    /// Serialize a single value.
+    fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String>;
+
+    /// Deserialize a single value by handing a deserializer over `ser_data` to `sink`, usually through
+    /// [feed_sink()].
+    fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String>;
+
+    /// Serialize the contents of the whole DB into the bytes of a DB file.
+    fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String>;
+
+    /// Deserialize the bytes of a DB file into the contents of the whole DB.
+    fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String>;
+
+    /// Whether a value can be deserialized without knowing its type, which json can do and bincode can't.
+    /// [Pickle::export_parquet()](struct.Pickle.html#method.export_parquet) writes the values of serializers
+    /// that can't as raw bytes instead of json. Defaults to `true`.
+    fn is_self_describing(&self) -> bool {
+        true
+    }
+}
+
+/// Hand a serde deserializer to the sink given to [PickleSerializer::deserialize_data()].
+pub fn feed_sink<'de, D>(deserializer: D, sink: &mut DataSink) -> Result<(), String>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut erased = <dyn erased_serde::Deserializer>::erase(deserializer);
+    sink(&mut erased).map_err(|err| err.to_string())
+}
+
+// Currently we will start with json serialization, because I unserstand it. Binary (bincode), yaml, cbor
+// and messagepack are available behind the `bincode`, `yaml`, `cbor` and `msgpack` features. Parquet is an
+// export format instead, see Pickle::export_parquet().
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum SerializationMethod {
     Json,
+    /// Json in the layout of Python's [pickleDB](https://pypi.org/project/pickleDB/), so both can share
+    /// a DB file. See [PickleDbSerializer] for how lists and dicts are told apart.
+    PickleDbCompat,
+    /// Binary serialization using [bincode](https://docs.rs/bincode). Requires the `bincode` feature.
+    #[cfg(feature = "bincode")]
+    Bin,
+    /// Human readable serialization using [serde_yaml](https://docs.rs/serde_yaml), meant for DB files
+    /// that are reviewed or edited by hand. Requires the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    Yaml,
+    /// Binary serialization using [CBOR](https://cbor.io) through [cbor4ii](https://docs.rs/cbor4ii).
+    /// Requires the `cbor` feature.
+    #[cfg(feature = "cbor")]
+    Cbor,
+    /// Binary serialization using [MessagePack](https://msgpack.org) through
+    /// [rmp-serde](https://docs.rs/rmp-serde). Requires the `msgpack` feature.
+    #[cfg(feature = "msgpack")]
+    MsgPack,
     // more stuff to come...
 }
 
+/// Ids 1 to 4 panic when the feature of their method isn't enabled, other unknown ids fall back to json.
 impl From<i32> for SerializationMethod {
     fn from(item: i32) -> Self {
         match item {
             0 => SerializationMethod::Json,
+            #[cfg(feature = "bincode")]
+            1 => SerializationMethod::Bin,
+            #[cfg(not(feature = "bincode"))]
+            1 => panic!("SerializationMethod 1 (Bin) requires the `bincode` feature"),
+            #[cfg(feature = "yaml")]
+            2 => SerializationMethod::Yaml,
+            #[cfg(not(feature = "yaml"))]
+            2 => panic!("SerializationMethod 2 (Yaml) requires the `yaml` feature"),
+            #[cfg(feature = "cbor")]
+            3 => SerializationMethod::Cbor,
+            #[cfg(not(feature = "cbor"))]
+            3 => panic!("SerializationMethod 3 (Cbor) requires the `cbor` feature"),
+            #[cfg(feature = "msgpack")]
+            4 => SerializationMethod::MsgPack,
+            #[cfg(not(feature = "msgpack"))]
+            4 => panic!("SerializationMethod 4 (MsgPack) requires the `msgpack` feature"),
+            5 => SerializationMethod::PickleDbCompat,
             _ => SerializationMethod::Json,
         }
     }
@@ -30,100 +188,665 @@ impl fmt::Display for SerializationMethod {
     }
 }
 
-struct JsonSerializer {}
+impl SerializationMethod {
+    fn into_serializer(self) -> Box<dyn PickleSerializer> {
+        match self {
+            SerializationMethod::Json => Box::new(JsonSerializer::new()),
+            SerializationMethod::PickleDbCompat => Box::new(PickleDbSerializer::new()),
+            #[cfg(feature = "bincode")]
+            SerializationMethod::Bin => Box::new(BincodeSerializer::new()),
+            #[cfg(feature = "yaml")]
+            SerializationMethod::Yaml => Box::new(YamlSerializer::new()),
+            #[cfg(feature = "cbor")]
+            SerializationMethod::Cbor => Box::new(CborSerializer::new()),
+            #[cfg(feature = "msgpack")]
+            SerializationMethod::MsgPack => Box::new(MsgPackSerializer::new()),
+        }
+    }
+}
+
+/// The serializer behind [SerializationMethod::Json].
+///
+/// The DB file is a json object with a `map`, `list_map` and `dict_map` field, where values are stored
+/// as real json. Files in the older layout, which stored values as json strings, are loaded as well.
+#[derive(Default)]
+pub struct JsonSerializer {}
 
 impl JsonSerializer {
-    fn new() -> JsonSerializer {
+    pub fn new() -> JsonSerializer {
         JsonSerializer {}
     }
+}
 
-    fn deserialize_data<V>(&self, ser_data: &[u8]) -> Option<V>
-    where
-        V: DeserializeOwned,
-    {
-        match serde_json::from_str(std::str::from_utf8(ser_data).unwrap()) {
-            Ok(val) => Some(val),
-            Err(_) => None,
+impl PickleSerializer for JsonSerializer {
+    fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String> {
+        match serde_json::to_string(data) {
+            Ok(ser_data) => Ok(ser_data.into_bytes()),
+            Err(err) => Err(err.to_string()),
         }
     }
 
-    fn serialize_data<V>(&self, data: &V) -> Result<Vec<u8>, String>
-    where
-        V: Serialize,
-    {
-        match serde_json::to_string(data) {
-            Ok(ser_data) => Ok(ser_data.into_bytes()),
+    fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String> {
+        let mut deserializer = serde_json::Deserializer::from_slice(ser_data);
+        feed_sink(&mut deserializer, sink)?;
+        deserializer.end().map_err(|err| err.to_string())
+    }
+
+    fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String> {
+        let to_value = |ser_data: &Vec<u8>| -> Result<serde_json::Value, String> {
+            serde_json::from_slice(ser_data).map_err(|err| err.to_string())
+        };
+
+        let mut json_db = JsonDb::default();
+        for (key, value) in db.map.iter() {
+            json_db.map.insert(key.to_string(), to_value(value)?);
+        }
+        for (key, list) in db.list_map.iter() {
+            let json_list = list.iter().map(to_value).collect::<Result<_, _>>()?;
+            json_db.list_map.insert(key.to_string(), json_list);
+        }
+        for (key, dict) in db.dict_map.iter() {
+            let mut json_dict = BTreeMap::new();
+            for (dict_key, item) in dict.iter() {
+                json_dict.insert(dict_key.to_string(), to_value(item)?);
+            }
+            json_db.dict_map.insert(key.to_string(), json_dict);
+        }
+        json_db
+            .expiry_map
+            .extend(db.expiry_map.iter().map(|(key, t)| (key.to_string(), *t)));
+
+        match serde_json::to_vec(&json_db) {
+            Ok(ser_db) => Ok(ser_db),
             Err(err) => Err(err.to_string()),
         }
     }
 
-    fn serialize_db(&self, map: &DbMap, list_map: &DbListMap) -> Result<Vec<u8>, String> {
-        let mut json_map: HashMap<&str, &str> = HashMap::new();
+    fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+        let json_value = match serde_json::from_slice::<serde_json::Value>(ser_db) {
+            Ok(json_value) => json_value,
+            Err(err) => return Err(err.to_string()),
+        };
+        // files written before values were stored as real json hold a tuple of maps instead of an object
+        if json_value.is_array() {
+            return deserialize_legacy_json_db(json_value);
+        }
+        let json_db = match serde_json::from_value::<JsonDb>(json_value) {
+            Ok(json_db) => json_db,
+            Err(err) => return Err(err.to_string()),
+        };
+
+        let from_value = |value: &serde_json::Value| -> Result<Vec<u8>, String> {
+            serde_json::to_vec(value).map_err(|err| err.to_string())
+        };
+
+        let mut db = DbContents::default();
+        for (key, value) in json_db.map.iter() {
+            db.map.insert(key.to_string(), from_value(value)?);
+        }
+        for (key, list) in json_db.list_map.iter() {
+            let byte_list = list.iter().map(from_value).collect::<Result<_, _>>()?;
+            db.list_map.insert(key.to_string(), byte_list);
+        }
+        for (key, dict) in json_db.dict_map.iter() {
+            let mut byte_dict = HashMap::new();
+            for (dict_key, item) in dict.iter() {
+                byte_dict.insert(dict_key.to_string(), from_value(item)?);
+            }
+            db.dict_map.insert(key.to_string(), byte_dict);
+        }
+        db.expiry_map.extend(json_db.expiry_map);
+        Ok(db)
+    }
+}
+
+// The old layout stored every value as a string holding its json. Such files are still loaded, and the next
+// dump writes them back in the current layout.
+fn deserialize_legacy_json_db(json_value: serde_json::Value) -> Result<DbContents, String> {
+    // files written before dicts were supported only hold the first two maps
+    let json_maps = match serde_json::from_value::<LegacyJsonDb>(json_value.clone()) {
+        Ok(maps) => maps,
+        Err(err) => match serde_json::from_value::<LegacyJsonDbWithoutDicts>(json_value) {
+            Ok((json_map, json_list_map)) => (json_map, json_list_map, HashMap::new()),
+            Err(_) => return Err(err.to_string()),
+        },
+    };
+
+    let (json_map, json_list_map, json_dict_map) = json_maps;
+    let mut byte_map: DbMap = HashMap::new();
+    for (key, value) in json_map.iter() {
+        byte_map.insert(key.to_string(), value.as_bytes().to_vec());
+    }
+
+    let mut byte_list_map: DbListMap = HashMap::new();
+    for (key, list) in json_list_map.iter() {
+        let byte_list: Vec<Vec<u8>> = list.iter().map(|item| item.as_bytes().to_vec()).collect();
+        byte_list_map.insert(key.to_string(), byte_list);
+    }
+
+    let mut byte_dict_map: DbDictMap = HashMap::new();
+    for (key, dict) in json_dict_map.iter() {
+        let byte_dict: HashMap<String, Vec<u8>> = dict
+            .iter()
+            .map(|(dict_key, item)| (dict_key.to_string(), item.as_bytes().to_vec()))
+            .collect();
+        byte_dict_map.insert(key.to_string(), byte_dict);
+    }
+    Ok(DbContents {
+        map: byte_map,
+        list_map: byte_list_map,
+        dict_map: byte_dict_map,
+        expiry_map: HashMap::new(),
+    })
+}
+
+// The DB file layout of the json serializer. Values are embedded as real json, and the maps are sorted by
+// key to keep the file stable between dumps. The expiry times are only written when some key expires.
+#[derive(Serialize, Deserialize, Default)]
+struct JsonDb {
+    #[serde(default)]
+    map: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    list_map: BTreeMap<String, Vec<serde_json::Value>>,
+    #[serde(default)]
+    dict_map: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    expiry_map: BTreeMap<String, u64>,
+}
 
-        for (key, value) in map.iter() {
-            json_map.insert(key, std::str::from_utf8(value).unwrap());
+/// The serializer behind [SerializationMethod::PickleDbCompat].
+///
+/// The DB file is a single json object like the one written by Python's
+/// [pickleDB](https://pypi.org/project/pickleDB/): lists are stored as json arrays and dicts as json
+/// objects, next to the plain values. When loading, every array becomes a list and every object becomes a
+/// dict, so a value that was `set` to an array or an object is read back as a list or a dict. There's no
+/// place for expiry times in this layout, so keys that expire are written without them.
+#[derive(Default)]
+pub struct PickleDbSerializer {
+    json: JsonSerializer,
+}
+
+impl PickleDbSerializer {
+    pub fn new() -> PickleDbSerializer {
+        PickleDbSerializer {
+            json: JsonSerializer::new(),
         }
+    }
+}
+
+impl PickleSerializer for PickleDbSerializer {
+    fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String> {
+        self.json.serialize_data(data)
+    }
 
-        let mut json_list_map: HashMap<&str, Vec<&str>> = HashMap::new();
-        for (key, list) in list_map.iter() {
-            let json_list: Vec<&str> = list
-                .iter()
-                .map(|item| std::str::from_utf8(item).unwrap())
-                .collect();
-            json_list_map.insert(key, json_list);
+    fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String> {
+        self.json.deserialize_data(ser_data, sink)
+    }
+
+    fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String> {
+        let to_value = |ser_data: &Vec<u8>| -> Result<serde_json::Value, String> {
+            serde_json::from_slice(ser_data).map_err(|err| err.to_string())
+        };
+
+        let mut json_db = serde_json::Map::new();
+        for (key, value) in db.map.iter() {
+            json_db.insert(key.to_string(), to_value(value)?);
         }
-        match serde_json::to_string(&(json_map, json_list_map)) {
-            Ok(ser_db) => Ok(ser_db.into_bytes()),
+        for (key, list) in db.list_map.iter() {
+            let json_list = list.iter().map(to_value).collect::<Result<_, _>>()?;
+            json_db.insert(key.to_string(), serde_json::Value::Array(json_list));
+        }
+        for (key, dict) in db.dict_map.iter() {
+            let mut json_dict = serde_json::Map::new();
+            for (dict_key, item) in dict.iter() {
+                json_dict.insert(dict_key.to_string(), to_value(item)?);
+            }
+            json_db.insert(key.to_string(), serde_json::Value::Object(json_dict));
+        }
+
+        match serde_json::to_vec(&json_db) {
+            Ok(ser_db) => Ok(ser_db),
             Err(err) => Err(err.to_string()),
         }
     }
 
-    fn deserialize_db(&self, ser_db: &[u8]) -> Result<(DbMap, DbListMap), String> {
-        match serde_json::from_str::<(HashMap<String, String>, HashMap<String, Vec<String>>)>(
-            std::str::from_utf8(ser_db).unwrap(),
-        ) {
-            Ok((json_map, json_list_map)) => {
-                let mut byte_map: DbMap = HashMap::new();
-                for (key, value) in json_map.iter() {
-                    byte_map.insert(key.to_string(), value.as_bytes().to_vec());
-                }
+    fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+        let json_db =
+            match serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(ser_db) {
+                Ok(json_db) => json_db,
+                Err(err) => return Err(err.to_string()),
+            };
 
-                let mut byte_list_map: DbListMap = HashMap::new();
-                for (key, list) in json_list_map.iter() {
-                    let byte_list: Vec<Vec<u8>> =
-                        list.iter().map(|item| item.as_bytes().to_vec()).collect();
-                    byte_list_map.insert(key.to_string(), byte_list);
+        let from_value = |value: &serde_json::Value| -> Result<Vec<u8>, String> {
+            serde_json::to_vec(value).map_err(|err| err.to_string())
+        };
+
+        let mut db = DbContents::default();
+        for (key, value) in json_db.iter() {
+            match value {
+                serde_json::Value::Array(list) => {
+                    let byte_list = list.iter().map(from_value).collect::<Result<_, _>>()?;
+                    db.list_map.insert(key.to_string(), byte_list);
+                }
+                serde_json::Value::Object(dict) => {
+                    let mut byte_dict = HashMap::new();
+                    for (dict_key, item) in dict.iter() {
+                        byte_dict.insert(dict_key.to_string(), from_value(item)?);
+                    }
+                    db.dict_map.insert(key.to_string(), byte_dict);
+                }
+                _ => {
+                    db.map.insert(key.to_string(), from_value(value)?);
                 }
-                Ok((byte_map, byte_list_map))
             }
+        }
+        Ok(db)
+    }
+}
+
+type LegacyJsonDbWithoutDicts = (HashMap<String, String>, HashMap<String, Vec<String>>);
+type LegacyJsonDb = (
+    HashMap<String, String>,
+    HashMap<String, Vec<String>>,
+    HashMap<String, HashMap<String, String>>,
+);
+
+// Values are stored as the raw bincode bytes, and the whole DB is written as a single bincode tuple of
+// the maps, so nothing goes through utf8 conversions like the json serializer does.
+/// The serializer behind [SerializationMethod::Bin].
+#[cfg(feature = "bincode")]
+#[derive(Default)]
+pub struct BincodeSerializer {}
+
+#[cfg(feature = "bincode")]
+impl BincodeSerializer {
+    pub fn new() -> BincodeSerializer {
+        BincodeSerializer {}
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl PickleSerializer for BincodeSerializer {
+    fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String> {
+        match bincode::serialize(data) {
+            Ok(ser_data) => Ok(ser_data),
+            Err(err) => Err(err.to_string()),
+        }
+    }
 
+    fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String> {
+        use bincode::Options;
+
+        // the encoding bincode::deserialize uses. A value must use up all of its bytes, or nearly any value
+        // would read as a shorter one, like an integer, and bincode only checks that in its own deserialize
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes();
+        let mut rest = ser_data;
+        {
+            let mut deserializer = bincode::Deserializer::with_reader(&mut rest, options);
+            feed_sink(&mut deserializer, sink)?;
+        }
+        if !rest.is_empty() {
+            return Err(format!("{} bytes left after the value", rest.len()));
+        }
+        Ok(())
+    }
+
+    // the expiry times are only added to the tuple when some key expires, so files without them keep the
+    // layout older versions read
+    fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String> {
+        let ser_db = match db.expiry_map.is_empty() {
+            true => bincode::serialize(&(db.map, db.list_map, db.dict_map)),
+            false => bincode::serialize(&(db.map, db.list_map, db.dict_map, db.expiry_map)),
+        };
+        match ser_db {
+            Ok(ser_db) => Ok(ser_db),
             Err(err) => Err(err.to_string()),
         }
     }
+
+    fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+        if let Ok((map, list_map, dict_map, expiry_map)) =
+            bincode::deserialize::<(DbMap, DbListMap, DbDictMap, DbExpiryMap)>(ser_db)
+        {
+            return Ok(DbContents {
+                map,
+                list_map,
+                dict_map,
+                expiry_map,
+            });
+        }
+        match bincode::deserialize::<(DbMap, DbListMap, DbDictMap)>(ser_db) {
+            Ok((map, list_map, dict_map)) => Ok(DbContents {
+                map,
+                list_map,
+                dict_map,
+                expiry_map: HashMap::new(),
+            }),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    // bincode only writes the bytes of a value, so they can't be read back as anything but its own type
+    fn is_self_describing(&self) -> bool {
+        false
+    }
+}
+
+// Values are stored as yaml documents. When writing the DB file every value is parsed back into a
+// yaml::Value, so the file holds real nested yaml instead of yaml strings containing yaml. The maps are
+// sorted by key to keep the file stable between dumps and easy to diff.
+/// The serializer behind [SerializationMethod::Yaml].
+#[cfg(feature = "yaml")]
+#[derive(Default)]
+pub struct YamlSerializer {}
+
+#[cfg(feature = "yaml")]
+#[derive(Serialize, Deserialize, Default)]
+struct YamlDb {
+    #[serde(default)]
+    map: BTreeMap<String, serde_yaml::Value>,
+    #[serde(default)]
+    list_map: BTreeMap<String, Vec<serde_yaml::Value>>,
+    #[serde(default)]
+    dict_map: BTreeMap<String, BTreeMap<String, serde_yaml::Value>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    expiry_map: BTreeMap<String, u64>,
+}
+
+#[cfg(feature = "yaml")]
+impl YamlSerializer {
+    pub fn new() -> YamlSerializer {
+        YamlSerializer {}
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl PickleSerializer for YamlSerializer {
+    fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String> {
+        match serde_yaml::to_string(data) {
+            Ok(ser_data) => Ok(ser_data.into_bytes()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String> {
+        feed_sink(serde_yaml::Deserializer::from_slice(ser_data), sink)
+    }
+
+    fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String> {
+        let to_value = |ser_data: &Vec<u8>| -> Result<serde_yaml::Value, String> {
+            serde_yaml::from_slice(ser_data).map_err(|err| err.to_string())
+        };
+
+        let mut yaml_db = YamlDb::default();
+        for (key, value) in db.map.iter() {
+            yaml_db.map.insert(key.to_string(), to_value(value)?);
+        }
+        for (key, list) in db.list_map.iter() {
+            let yaml_list = list.iter().map(to_value).collect::<Result<_, _>>()?;
+            yaml_db.list_map.insert(key.to_string(), yaml_list);
+        }
+        for (key, dict) in db.dict_map.iter() {
+            let mut yaml_dict = BTreeMap::new();
+            for (dict_key, item) in dict.iter() {
+                yaml_dict.insert(dict_key.to_string(), to_value(item)?);
+            }
+            yaml_db.dict_map.insert(key.to_string(), yaml_dict);
+        }
+        yaml_db
+            .expiry_map
+            .extend(db.expiry_map.iter().map(|(key, t)| (key.to_string(), *t)));
+
+        match serde_yaml::to_string(&yaml_db) {
+            Ok(ser_db) => Ok(ser_db.into_bytes()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+        let from_value =
+            |value: &serde_yaml::Value| -> Result<Vec<u8>, String> { self.serialize_data(value) };
+
+        let yaml_db = match serde_yaml::from_slice::<Option<YamlDb>>(ser_db) {
+            // an empty file is an empty DB
+            Ok(yaml_db) => yaml_db.unwrap_or_default(),
+            Err(err) => return Err(err.to_string()),
+        };
+
+        let mut db = DbContents::default();
+        for (key, value) in yaml_db.map.iter() {
+            db.map.insert(key.to_string(), from_value(value)?);
+        }
+        for (key, list) in yaml_db.list_map.iter() {
+            let byte_list = list.iter().map(from_value).collect::<Result<_, _>>()?;
+            db.list_map.insert(key.to_string(), byte_list);
+        }
+        for (key, dict) in yaml_db.dict_map.iter() {
+            let mut byte_dict = HashMap::new();
+            for (dict_key, item) in dict.iter() {
+                byte_dict.insert(dict_key.to_string(), from_value(item)?);
+            }
+            db.dict_map.insert(key.to_string(), byte_dict);
+        }
+        db.expiry_map.extend(yaml_db.expiry_map);
+        Ok(db)
+    }
+}
+
+// cbor and messagepack both write the DB as a tuple of the maps like bincode does, with the expiry times
+// only added when some key expires. The stored values are wrapped as byte strings so they are written as a
+// single blob instead of an array of small integers.
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+type ByteDb<'a> = (
+    HashMap<&'a str, &'a serde_bytes::Bytes>,
+    HashMap<&'a str, Vec<&'a serde_bytes::Bytes>>,
+    HashMap<&'a str, HashMap<&'a str, &'a serde_bytes::Bytes>>,
+);
+
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+type ByteBufDbWithoutExpiry = (
+    HashMap<String, serde_bytes::ByteBuf>,
+    HashMap<String, Vec<serde_bytes::ByteBuf>>,
+    HashMap<String, HashMap<String, serde_bytes::ByteBuf>>,
+);
+
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+type ByteBufDb = (
+    HashMap<String, serde_bytes::ByteBuf>,
+    HashMap<String, Vec<serde_bytes::ByteBuf>>,
+    HashMap<String, HashMap<String, serde_bytes::ByteBuf>>,
+    DbExpiryMap,
+);
+
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+fn serialize_byte_db(
+    serializer: &dyn PickleSerializer,
+    db: DbContentsRef,
+) -> Result<Vec<u8>, String> {
+    let (map, list_map, dict_map) = to_byte_db(db);
+    match db.expiry_map.is_empty() {
+        true => serializer.serialize_data(&(map, list_map, dict_map)),
+        false => serializer.serialize_data(&(map, list_map, dict_map, db.expiry_map)),
+    }
+}
+
+// files written before keys could expire hold just the first three maps
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+fn without_expiry(byte_db: ByteBufDbWithoutExpiry) -> ByteBufDb {
+    let (map, list_map, dict_map) = byte_db;
+    (map, list_map, dict_map, HashMap::new())
+}
+
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+fn to_byte_db<'a>(db: DbContentsRef<'a>) -> ByteDb<'a> {
+    let bytes = |value: &'a Vec<u8>| serde_bytes::Bytes::new(value);
+    (
+        db.map
+            .iter()
+            .map(|(key, value)| (key.as_str(), bytes(value)))
+            .collect(),
+        db.list_map
+            .iter()
+            .map(|(key, list)| (key.as_str(), list.iter().map(bytes).collect()))
+            .collect(),
+        db.dict_map
+            .iter()
+            .map(|(key, dict)| {
+                let byte_dict = dict
+                    .iter()
+                    .map(|(dict_key, item)| (dict_key.as_str(), bytes(item)))
+                    .collect();
+                (key.as_str(), byte_dict)
+            })
+            .collect(),
+    )
 }
 
-// crate a struct to hold all of our serialization methods. Right now that's just json.
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+fn from_byte_buf_db(byte_db: ByteBufDb) -> DbContents {
+    let (byte_map, byte_list_map, byte_dict_map, expiry_map) = byte_db;
+    DbContents {
+        map: byte_map
+            .into_iter()
+            .map(|(key, value)| (key, value.into_vec()))
+            .collect(),
+        list_map: byte_list_map
+            .into_iter()
+            .map(|(key, list)| (key, list.into_iter().map(|item| item.into_vec()).collect()))
+            .collect(),
+        dict_map: byte_dict_map
+            .into_iter()
+            .map(|(key, dict)| {
+                let dict = dict
+                    .into_iter()
+                    .map(|(dict_key, item)| (dict_key, item.into_vec()))
+                    .collect();
+                (key, dict)
+            })
+            .collect(),
+        expiry_map,
+    }
+}
+
+// ciborium doesn't expose its deserializer, which erased deserialization needs, so cbor goes through
+// cbor4ii instead. Both write plain CBOR, so files are readable by either.
+/// The serializer behind [SerializationMethod::Cbor].
+#[cfg(feature = "cbor")]
+#[derive(Default)]
+pub struct CborSerializer {}
+
+#[cfg(feature = "cbor")]
+impl CborSerializer {
+    pub fn new() -> CborSerializer {
+        CborSerializer {}
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl PickleSerializer for CborSerializer {
+    fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String> {
+        match cbor4ii::serde::to_vec(Vec::new(), &data) {
+            Ok(ser_data) => Ok(ser_data),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String> {
+        let reader = cbor4ii::core::utils::SliceReader::new(ser_data);
+        let mut deserializer = cbor4ii::serde::Deserializer::new(reader);
+        feed_sink(&mut deserializer, sink)
+    }
+
+    fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String> {
+        serialize_byte_db(self, db)
+    }
+
+    fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+        let byte_db = match cbor4ii::serde::from_slice::<ByteBufDb>(ser_db) {
+            Ok(byte_db) => Ok(byte_db),
+            Err(_) => {
+                cbor4ii::serde::from_slice::<ByteBufDbWithoutExpiry>(ser_db).map(without_expiry)
+            }
+        };
+        match byte_db {
+            Ok(byte_db) => Ok(from_byte_buf_db(byte_db)),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+/// The serializer behind [SerializationMethod::MsgPack].
+#[cfg(feature = "msgpack")]
+#[derive(Default)]
+pub struct MsgPackSerializer {}
+
+#[cfg(feature = "msgpack")]
+impl MsgPackSerializer {
+    pub fn new() -> MsgPackSerializer {
+        MsgPackSerializer {}
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl PickleSerializer for MsgPackSerializer {
+    // structs are written as maps with named fields, which is what other messagepack libraries expect
+    fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String> {
+        match rmp_serde::to_vec_named(data) {
+            Ok(ser_data) => Ok(ser_data),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String> {
+        let mut deserializer = rmp_serde::Deserializer::from_read_ref(ser_data);
+        feed_sink(&mut deserializer, sink)
+    }
+
+    fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String> {
+        serialize_byte_db(self, db)
+    }
+
+    fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+        let byte_db = match rmp_serde::from_slice::<ByteBufDb>(ser_db) {
+            Ok(byte_db) => Ok(byte_db),
+            Err(_) => rmp_serde::from_slice::<ByteBufDbWithoutExpiry>(ser_db).map(without_expiry),
+        };
+        match byte_db {
+            Ok(byte_db) => Ok(from_byte_buf_db(byte_db)),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+// crate a struct to hold the serializer of a DB and give its dyn-safe methods back their types.
 pub(crate) struct Serializer {
-    ser_method: SerializationMethod,
-    json_serializer: JsonSerializer,
+    inner: Box<dyn PickleSerializer>,
 }
 
 impl Serializer {
     pub(crate) fn new(ser_method: SerializationMethod) -> Serializer {
-        Serializer {
-            ser_method,
-            json_serializer: JsonSerializer::new(),
-            // deserialize_data
-            // serialize_data
-        }
+        Serializer::with(ser_method.into_serializer())
+    }
+
+    pub(crate) fn with(serializer: Box<dyn PickleSerializer>) -> Serializer {
+        Serializer { inner: serializer }
     }
 
     pub(crate) fn deserialize_data<V>(&self, ser_data: &[u8]) -> Option<V>
     where
         V: DeserializeOwned,
     {
-        match self.ser_method {
-            SerializationMethod::Json => self.json_serializer.deserialize_data(ser_data),
+        let mut value = None;
+        let result = self.inner.deserialize_data(ser_data, &mut |deserializer| {
+            value = Some(erased_serde::deserialize::<V>(deserializer)?);
+            Ok(())
+        });
+
+        match result {
+            Ok(_) => value,
+            Err(_) => None,
         }
     }
 
@@ -131,24 +854,30 @@ impl Serializer {
     where
         V: Serialize,
     {
-        match self.ser_method {
-            SerializationMethod::Json => self.json_serializer.serialize_data(data),
-        }
+        self.inner.serialize_data(data)
     }
     // we need these for the dumpdb calls in rustypickle.rs
     pub(crate) fn serialize_db(
         &self,
         map: &DbMap,
         list_map: &DbListMap,
+        dict_map: &DbDictMap,
+        expiry_map: &DbExpiryMap,
     ) -> Result<Vec<u8>, String> {
-        match self.ser_method {
-            SerializationMethod::Json => self.json_serializer.serialize_db(map, list_map),
-        }
+        self.inner.serialize_db(DbContentsRef {
+            map,
+            list_map,
+            dict_map,
+            expiry_map,
+        })
     }
 
-    pub(crate) fn deserialize_db(&self, ser_db: &[u8]) -> Result<(DbMap, DbListMap), String> {
-        match self.ser_method {
-            SerializationMethod::Json => self.json_serializer.deserialize_db(ser_db),
-        }
+    pub(crate) fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+        self.inner.deserialize_db(ser_db)
+    }
+
+    #[cfg(feature = "parquet")]
+    pub(crate) fn is_self_describing(&self) -> bool {
+        self.inner.is_self_describing()
     }
 }
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::rustypickle::Pickle;
+
+/// A `Pickle` that can be cloned and shared between threads.
+///
+/// Reads take a shared lock, so they run in parallel with each other, and writes take an exclusive lock.
+/// Every write goes through the methods of the `Pickle` inside, so the dump policy works just like it does
+/// for a single `Pickle`. The `Pickle` is dropped, and its pending changes dumped, along with the last
+/// clone.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rusty_pickle::{DumpPolicy, Pickle, SerializationMethod, SharedPickle};
+///
+/// let db = SharedPickle::new(Pickle::new("example.db", DumpPolicy::Auto, SerializationMethod::Json));
+/// let writer = db.clone();
+/// std::thread::spawn(move || writer.set("key1", &100).unwrap()).join().unwrap();
+/// assert_eq!(db.get::<i32>("key1"), Some(100));
+/// ```
+#[derive(Clone)]
+pub struct SharedPickle {
+    db: Arc<RwLock<Pickle>>,
+}
+
+impl SharedPickle {
+    /// Share `db` between threads.
+    pub fn new(db: Pickle) -> SharedPickle {
+        SharedPickle {
+            db: Arc::new(RwLock::new(db)),
+        }
+    }
+
+    /// Lock the DB for reading, for the methods that don't have a counterpart on `SharedPickle`.
+    pub fn read(&self) -> RwLockReadGuard<'_, Pickle> {
+        self.db.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the DB for writing, for the methods that don't have a counterpart on `SharedPickle` or to make
+    /// several changes without other threads seeing the ones in between.
+    pub fn write(&self) -> RwLockWriteGuard<'_, Pickle> {
+        self.db.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// See [Pickle::get](struct.Pickle.html#method.get).
+    pub fn get<V>(&self, key: &str) -> Option<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.read().get(key)
+    }
+
+    /// See [Pickle::exists](struct.Pickle.html#method.exists).
+    pub fn exists(&self, key: &str) -> bool {
+        self.read().exists(key)
+    }
+
+    /// See [Pickle::key_count](struct.Pickle.html#method.key_count).
+    pub fn key_count(&self) -> usize {
+        self.read().key_count()
+    }
+
+    /// See [Pickle::list_keys](struct.Pickle.html#method.list_keys).
+    pub fn list_keys(&self) -> Vec<String> {
+        self.read().list_keys()
+    }
+
+    /// See [Pickle::lget](struct.Pickle.html#method.lget).
+    pub fn lget<V>(&self, name: &str, pos: usize) -> Option<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.read().lget(name, pos)
+    }
+
+    /// See [Pickle::lgetall](struct.Pickle.html#method.lgetall).
+    pub fn lgetall<V>(&self, name: &str) -> Option<Vec<V>>
+    where
+        V: DeserializeOwned,
+    {
+        self.read().lgetall(name)
+    }
+
+    /// See [Pickle::llen](struct.Pickle.html#method.llen).
+    pub fn llen(&self, name: &str) -> usize {
+        self.read().llen(name)
+    }
+
+    /// See [Pickle::dget](struct.Pickle.html#method.dget).
+    pub fn dget<V>(&self, name: &str, key: &str) -> Option<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.read().dget(name, key)
+    }
+
+    /// See [Pickle::dgetall](struct.Pickle.html#method.dgetall).
+    pub fn dgetall<V>(&self, name: &str) -> Option<HashMap<String, V>>
+    where
+        V: DeserializeOwned,
+    {
+        self.read().dgetall(name)
+    }
+
+    /// See [Pickle::dexists](struct.Pickle.html#method.dexists).
+    pub fn dexists(&self, name: &str, key: &str) -> bool {
+        self.read().dexists(name, key)
+    }
+
+    /// See [Pickle::set](struct.Pickle.html#method.set).
+    pub fn set<V>(&self, key: &str, value: &V) -> Result<()>
+    where
+        V: Serialize,
+    {
+        self.write().set(key, value)
+    }
+
+    /// See [Pickle::remove](struct.Pickle.html#method.remove).
+    pub fn remove(&self, key: &str) -> Result<bool> {
+        self.write().remove(key)
+    }
+
+    /// See [Pickle::dump](struct.Pickle.html#method.dump).
+    pub fn dump(&self) -> Result<()> {
+        self.write().dump()
+    }
+
+    /// See [Pickle::lpop](struct.Pickle.html#method.lpop).
+    pub fn lpop<V>(&self, name: &str, pos: isize) -> Result<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.write().lpop(name, pos)
+    }
+
+    /// See [Pickle::lremlist](struct.Pickle.html#method.lremlist).
+    pub fn lremlist<V>(&self, name: &str) -> Result<Vec<V>>
+    where
+        V: DeserializeOwned,
+    {
+        self.write().lremlist(name)
+    }
+
+    /// See [Pickle::dpop](struct.Pickle.html#method.dpop).
+    pub fn dpop<V>(&self, name: &str, key: &str) -> Result<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.write().dpop(name, key)
+    }
+
+    /// See [Pickle::drem](struct.Pickle.html#method.drem).
+    pub fn drem(&self, name: &str) -> Result<bool> {
+        self.write().drem(name)
+    }
+}
@@ -0,0 +1,55 @@
+// Test doubles shared by the integration tests.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rusty_pickle::{
+    erased_serde, DataSink, DbContents, DbContentsRef, DumpPolicy, JsonSerializer, Pickle,
+    PickleSerializer,
+};
+
+// a serializer that writes plain json, counts the DB dumps and can be told to fail them
+pub struct CountingSerializer {
+    json: JsonSerializer,
+    dumps: Arc<AtomicUsize>,
+    fail: Arc<AtomicBool>,
+}
+
+impl PickleSerializer for CountingSerializer {
+    fn serialize_data(&self, data: &dyn erased_serde::Serialize) -> Result<Vec<u8>, String> {
+        self.json.serialize_data(data)
+    }
+
+    fn deserialize_data(&self, ser_data: &[u8], sink: &mut DataSink) -> Result<(), String> {
+        self.json.deserialize_data(ser_data, sink)
+    }
+
+    fn serialize_db(&self, db: DbContentsRef) -> Result<Vec<u8>, String> {
+        if self.fail.load(Ordering::SeqCst) {
+            return Err(String::from("dump failed on purpose"));
+        }
+        self.dumps.fetch_add(1, Ordering::SeqCst);
+        self.json.serialize_db(db)
+    }
+
+    fn deserialize_db(&self, ser_db: &[u8]) -> Result<DbContents, String> {
+        self.json.deserialize_db(ser_db)
+    }
+}
+
+/// A DB under `DumpPolicy::Auto` with a `CountingSerializer`, along with its count of dumps and the flag
+/// that makes them fail.
+pub fn counting_db(db_path: &str) -> (Pickle, Arc<AtomicUsize>, Arc<AtomicBool>) {
+    let dumps = Arc::new(AtomicUsize::new(0));
+    let fail = Arc::new(AtomicBool::new(false));
+    let db = Pickle::with_serializer(
+        db_path,
+        DumpPolicy::Auto,
+        Box::new(CountingSerializer {
+            json: JsonSerializer::new(),
+            dumps: Arc::clone(&dumps),
+            fail: Arc::clone(&fail),
+        }),
+    );
+    (db, dumps, fail)
+}
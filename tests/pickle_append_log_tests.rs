@@ -0,0 +1,203 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::{CompactionThreshold, DumpPolicy};
+
+    use super::*;
+
+    #[test]
+    fn test_append_log_replay() {
+        let mut db = Pickle::new(
+            "test_append_log.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &100).unwrap();
+        db.set("key2", &String::from("hello")).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+        db.lpop::<i32>("list1", 0).unwrap();
+        db.dcreate("dict1").unwrap().dadd("a", &1).unwrap();
+        db.set("key1", &200).unwrap();
+        db.remove("key2").unwrap();
+        db.dcreate("key3").unwrap();
+        db.set("key3", &3).unwrap();
+
+        // only the first change was written to the DB file, the rest went to the log
+        let content = std::fs::read_to_string("test_append_log.db").unwrap();
+        assert!(content.contains(r#""key1":100"#));
+        assert!(!content.contains("list1"));
+        assert!(std::fs::metadata("test_append_log.db.log").unwrap().len() > 0);
+
+        let db2 = Pickle::load_json("test_append_log.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 200);
+        assert!(!db2.exists("key2"));
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![2, 3]);
+        assert_eq!(db2.dget::<i32>("dict1", "a").unwrap(), 1);
+        assert_eq!(db2.get::<i32>("key3").unwrap(), 3);
+        assert!(!db2.dexists("key3", "a"));
+        assert_eq!(db2.key_count(), 4);
+
+        std::fs::remove_file("test_append_log.db").unwrap();
+        std::fs::remove_file("test_append_log.db.log").unwrap();
+    }
+
+    #[test]
+    fn test_append_log_dump_empties_log() {
+        let mut db = Pickle::new(
+            "test_append_log_dump.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        db.set("key2", &2).unwrap();
+        db.dump().unwrap();
+        assert_eq!(
+            std::fs::metadata("test_append_log_dump.db.log")
+                .unwrap()
+                .len(),
+            0
+        );
+
+        db.set("key3", &3).unwrap();
+        let mut db2 = Pickle::load_json("test_append_log_dump.db", DumpPolicy::Auto).unwrap();
+        assert_eq!(db2.key_count(), 3);
+
+        // dumping the whole DB under any other policy removes the log
+        db2.set("key4", &4).unwrap();
+        assert!(!std::path::Path::new("test_append_log_dump.db.log").exists());
+        let db3 = Pickle::load_json("test_append_log_dump.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db3.key_count(), 4);
+        std::fs::remove_file("test_append_log_dump.db").unwrap();
+    }
+
+    #[test]
+    fn test_append_log_torn_record() {
+        let mut db = Pickle::new(
+            "test_append_log_torn.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        db.set("key2", &2).unwrap();
+        let log_len = std::fs::metadata("test_append_log_torn.db.log")
+            .unwrap()
+            .len();
+
+        // what a crash in the middle of appending a record leaves behind
+        let mut log = std::fs::OpenOptions::new()
+            .append(true)
+            .open("test_append_log_torn.db.log")
+            .unwrap();
+        log.write_all(&[2, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0, 0, b'['])
+            .unwrap();
+
+        let mut db2 = Pickle::load_json("test_append_log_torn.db", DumpPolicy::AppendLog).unwrap();
+        assert_eq!(db2.get::<i32>("key2").unwrap(), 2);
+        assert_eq!(
+            std::fs::metadata("test_append_log_torn.db.log")
+                .unwrap()
+                .len(),
+            log_len
+        );
+
+        // the torn record is dropped, so new records can be read back
+        db2.set("key3", &3).unwrap();
+        let db3 = Pickle::load_json("test_append_log_torn.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db3.key_count(), 3);
+        std::fs::remove_file("test_append_log_torn.db").unwrap();
+        std::fs::remove_file("test_append_log_torn.db.log").unwrap();
+    }
+
+    #[test]
+    fn test_append_log_corrupt_record() {
+        let mut db = Pickle::new(
+            "test_append_log_corrupt.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        db.set("key2", &2).unwrap();
+        db.set("key3", &3).unwrap();
+
+        // break the first of the two records in the log
+        let mut log = std::fs::read("test_append_log_corrupt.db.log").unwrap();
+        log[16] = b'x';
+        std::fs::write("test_append_log_corrupt.db.log", log).unwrap();
+
+        let err = Pickle::load_json("test_append_log_corrupt.db", DumpPolicy::Never)
+            .err()
+            .unwrap();
+        assert!(matches!(err.get_type(), ErrorType::Serialization));
+        std::fs::remove_file("test_append_log_corrupt.db").unwrap();
+        std::fs::remove_file("test_append_log_corrupt.db.log").unwrap();
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut db = Pickle::new(
+            "test_compact.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+        db.remove("key1").unwrap();
+        db.set("key2", &2).unwrap();
+        let log = std::fs::read("test_compact.db.log").unwrap();
+
+        db.compact().unwrap();
+        assert_eq!(std::fs::metadata("test_compact.db.log").unwrap().len(), 0);
+        let db2 = Pickle::load_json("test_compact.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert_eq!(db2.key_count(), 2);
+
+        // a crash after the new DB file is in place but before the log is emptied loses nothing
+        std::fs::write("test_compact.db.log", log).unwrap();
+        let db3 = Pickle::load_json("test_compact.db", DumpPolicy::Never).unwrap();
+        assert!(!db3.exists("key1"));
+        assert_eq!(db3.get::<i32>("key2").unwrap(), 2);
+        assert_eq!(db3.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert_eq!(db3.key_count(), 2);
+        std::fs::remove_file("test_compact.db").unwrap();
+        std::fs::remove_file("test_compact.db.log").unwrap();
+    }
+
+    #[test]
+    fn test_compaction_threshold() {
+        let mut db = Pickle::new(
+            "test_compaction_threshold.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set_compaction_threshold(CompactionThreshold::LogBytes(500));
+        for i in 0..100 {
+            db.set("key1", &i).unwrap();
+            let log_len = std::fs::metadata("test_compaction_threshold.db.log")
+                .unwrap()
+                .len();
+            assert!(log_len <= 500);
+        }
+
+        db.set_compaction_threshold(CompactionThreshold::SnapshotRatio(2.0));
+        for i in 0..100 {
+            db.set(&format!("key{}", i), &i).unwrap();
+            let log_len = std::fs::metadata("test_compaction_threshold.db.log")
+                .unwrap()
+                .len();
+            let snapshot_len = std::fs::metadata("test_compaction_threshold.db")
+                .unwrap()
+                .len();
+            assert!(log_len <= snapshot_len * 2);
+        }
+
+        let db2 = Pickle::load_json("test_compaction_threshold.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.key_count(), 100);
+        assert_eq!(db2.get::<i32>("key99").unwrap(), 99);
+        std::fs::remove_file("test_compaction_threshold.db").unwrap();
+        std::fs::remove_file("test_compaction_threshold.db.log").unwrap();
+    }
+}
@@ -0,0 +1,127 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(all(test, feature = "tokio"))]
+mod common;
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::{AsyncPickle, DumpPolicy};
+
+    use super::*;
+    use crate::common::counting_db;
+
+    async fn wait_for_dump(db: &AsyncPickle) {
+        while db.is_dirty().await {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_auto_dump_coalesced() {
+        let (db, dumps, _) = counting_db("test_async_coalesced.db");
+        let db = AsyncPickle::from_pickle(db);
+
+        // a burst of changes is written with a single dump
+        for i in 0..10 {
+            db.set(&format!("key{}", i), &i).await.unwrap();
+        }
+        assert!(db.remove("key9").await.unwrap());
+        wait_for_dump(&db).await;
+        assert_eq!(dumps.load(Ordering::SeqCst), 1);
+        let db2 = Pickle::load_json("test_async_coalesced.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.key_count(), 9);
+
+        db.set("key1", &100).await.unwrap();
+        assert_eq!(db.get::<i32>("key1").await, Some(100));
+        db.close().await.unwrap();
+        assert_eq!(dumps.load(Ordering::SeqCst), 2);
+        let db3 = Pickle::load_json("test_async_coalesced.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db3.get::<i32>("key1").unwrap(), 100);
+        std::fs::remove_file("test_async_coalesced.db").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_async_background_dump_error() {
+        let (db, _, fail) = counting_db("test_async_dump_error.db");
+        let db = AsyncPickle::from_pickle(db);
+        fail.store(true, Ordering::SeqCst);
+        db.set("key1", &1).await.unwrap();
+
+        // the error of the background dump is returned by a later call, which doesn't make its change
+        let err = loop {
+            match db.set("key2", &2).await {
+                Ok(_) => tokio::task::yield_now().await,
+                Err(err) => break err,
+            }
+        };
+        assert!(matches!(err.get_type(), ErrorType::Serialization));
+        assert!(db.is_dirty().await);
+
+        // the failed changes are dumped along with the next ones
+        fail.store(false, Ordering::SeqCst);
+        db.set("key3", &3).await.unwrap();
+        db.close().await.unwrap();
+        let db2 = Pickle::load_json("test_async_dump_error.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key1"), Some(1));
+        assert_eq!(db2.get::<i32>("key3"), Some(3));
+        std::fs::remove_file("test_async_dump_error.db").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_async_load_and_dump() {
+        let mut db = Pickle::new(
+            "test_async_load.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        drop(db);
+
+        let db = AsyncPickle::load(
+            "test_async_load.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        )
+        .await
+        .unwrap();
+        assert!(db.exists("key1").await);
+        db.set("key2", &2).await.unwrap();
+        assert!(db.is_dirty().await);
+        db.dump().await.unwrap();
+        assert!(!db.is_dirty().await);
+
+        let db2 = AsyncPickle::load(
+            "test_async_load.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        )
+        .await
+        .unwrap();
+        assert_eq!(db2.get::<i32>("key2").await, Some(2));
+        assert!(db2.remove("key1").await.unwrap());
+        db2.close().await.unwrap();
+
+        let db3 = Pickle::load_json("test_async_load.db", DumpPolicy::Never).unwrap();
+        assert!(!db3.exists("key1"));
+        std::fs::remove_file("test_async_load.db").unwrap();
+        std::fs::remove_file("test_async_load.db.log").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_async_close_reports_error() {
+        std::fs::create_dir_all("test_async_close_dir").unwrap();
+        let db = AsyncPickle::new(
+            "test_async_close_dir/test.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).await.unwrap();
+
+        // the final dump fails because the DB directory is gone
+        std::fs::remove_dir_all("test_async_close_dir").unwrap();
+        assert!(db.close().await.is_err());
+    }
+}
@@ -0,0 +1,82 @@
+pub use rusty_pickle::Pickle;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+    use crate::common::counting_db;
+
+    #[test]
+    fn test_batch() {
+        let (mut db, dumps, _) = counting_db("test_batch.db");
+        let mut batch = db.batch();
+        for i in 0..1000 {
+            batch.set(&format!("key{}", i), &i).unwrap();
+        }
+        batch.lcreate("list1").unwrap().lextend(&[1, 2]);
+        assert_eq!(batch.key_count(), 1001);
+        batch.commit().unwrap();
+        assert_eq!(dumps.load(Ordering::SeqCst), 1);
+
+        // dropping the batch dumps it as well
+        {
+            let mut batch = db.batch();
+            batch.remove("key0").unwrap();
+            batch.remove("key1").unwrap();
+        }
+        assert_eq!(dumps.load(Ordering::SeqCst), 2);
+        let db2 = Pickle::load_json("test_batch.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.key_count(), 999);
+        std::fs::remove_file("test_batch.db").unwrap();
+    }
+
+    #[test]
+    fn test_batch_dump_fails() {
+        let (mut db, dumps, fail) = counting_db("test_batch_fails.db");
+        fail.store(true, Ordering::SeqCst);
+        let mut batch = db.batch();
+        batch.set("key1", &1).unwrap();
+        batch.set("key2", &2).unwrap();
+        let err = batch.commit().unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::Serialization));
+
+        // the changes are kept for the next dump
+        assert_eq!(db.get::<i32>("key2").unwrap(), 2);
+        assert_eq!(db.pending_changes(), 2);
+        fail.store(false, Ordering::SeqCst);
+        db.dump().unwrap();
+        assert_eq!(dumps.load(Ordering::SeqCst), 1);
+        assert!(!db.is_dirty());
+        std::fs::remove_file("test_batch_fails.db").unwrap();
+    }
+
+    #[test]
+    fn test_set_and_remove_many() {
+        let (mut db, dumps, fail) = counting_db("test_set_many.db");
+        db.set_many((0..100).map(|i| (format!("key{}", i), i)))
+            .unwrap();
+        assert_eq!(db.key_count(), 100);
+        assert_eq!(db.get::<i32>("key99").unwrap(), 99);
+        assert_eq!(dumps.load(Ordering::SeqCst), 1);
+
+        assert_eq!(db.remove_many(["key0", "key1", "no_such_key"]).unwrap(), 2);
+        assert_eq!(db.key_count(), 98);
+        assert_eq!(dumps.load(Ordering::SeqCst), 2);
+
+        // a failed dump leaves all of the keys as they were
+        fail.store(true, Ordering::SeqCst);
+        assert!(db.set_many(vec![("key2", 200), ("key100", 100)]).is_err());
+        assert_eq!(db.get::<i32>("key2").unwrap(), 2);
+        assert!(!db.exists("key100"));
+        assert!(db.remove_many(vec!["key2", "key3"]).is_err());
+        assert_eq!(db.key_count(), 98);
+        assert!(!db.is_dirty());
+        std::fs::remove_file("test_set_many.db").unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+
+    #[test]
+    fn test_set_if_absent_present() {
+        let mut db = Pickle::new(
+            "test_set_if_absent.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        assert!(!db.set_if_present("key1", &1).unwrap());
+        assert!(!db.exists("key1"));
+        assert!(db.set_if_absent("key1", &1).unwrap());
+        assert!(!db.set_if_absent("key1", &2).unwrap());
+        assert_eq!(db.get::<i32>("key1"), Some(1));
+        assert!(db.set_if_present("key1", &3).unwrap());
+        assert_eq!(db.get::<i32>("key1"), Some(3));
+
+        // lists and dicts exist too
+        db.lcreate("list1").unwrap();
+        assert!(!db.set_if_absent("list1", &1).unwrap());
+        assert_eq!(db.pending_changes(), 3);
+
+        // expired keys don't
+        db.set_with_ttl("session", &1, Duration::from_millis(1))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(!db.set_if_present("session", &2).unwrap());
+        assert!(db.set_if_absent("session", &3).unwrap());
+        assert_eq!(db.get::<i32>("session"), Some(3));
+        assert!(db.ttl("session").is_none());
+        drop(db);
+        std::fs::remove_file("test_set_if_absent.db").unwrap();
+    }
+
+    #[test]
+    fn test_getset() {
+        let mut db = Pickle::new(
+            "test_getset.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        assert_eq!(db.getset("counter", &5).unwrap(), None);
+        assert_eq!(db.getset("counter", &0).unwrap(), Some(5));
+        assert_eq!(db.get::<i32>("counter"), Some(0));
+
+        db.set("text", &"abc").unwrap();
+        db.lcreate("list1").unwrap();
+        for key in ["text", "list1"] {
+            let err = db.getset(key, &1).unwrap_err();
+            assert!(matches!(err.get_type(), ErrorType::WrongType));
+        }
+        assert_eq!(db.get::<String>("text").unwrap(), "abc");
+        assert!(db.exists("list1"));
+
+        let loaded = Pickle::load_json("test_getset.db", DumpPolicy::Never).unwrap();
+        assert_eq!(loaded.get::<i32>("counter"), Some(0));
+        std::fs::remove_file("test_getset.db").unwrap();
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        std::fs::create_dir_all("test_cas_dir").unwrap();
+        let mut db = Pickle::new(
+            "test_cas_dir/test.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        assert!(!db.compare_and_swap("job1", &"queued", &"running").unwrap());
+        assert!(!db.exists("job1"));
+
+        db.set("job1", &"queued").unwrap();
+        assert!(!db.compare_and_swap("job1", &"done", &"running").unwrap());
+        assert!(db.compare_and_swap("job1", &"queued", &"running").unwrap());
+        assert_eq!(db.get::<String>("job1").unwrap(), "running");
+
+        let loaded = Pickle::load_json("test_cas_dir/test.db", DumpPolicy::Never).unwrap();
+        assert_eq!(loaded.get::<String>("job1").unwrap(), "running");
+
+        // a failed dump restores the old value
+        std::fs::remove_dir_all("test_cas_dir").unwrap();
+        assert!(db.compare_and_swap("job1", &"running", &"done").is_err());
+        assert_eq!(db.get::<String>("job1").unwrap(), "running");
+    }
+}
@@ -0,0 +1,128 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+
+    #[test]
+    fn test_incr_decr() {
+        let mut db = Pickle::new(
+            "test_incr_decr.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        assert_eq!(db.incr("counter", 5).unwrap(), 5);
+        assert_eq!(db.incr("counter", 2).unwrap(), 7);
+        assert_eq!(db.decr("counter", 10).unwrap(), -3);
+        assert_eq!(db.decr("other", 1).unwrap(), -1);
+
+        assert!((db.incr_float("float", 1.5).unwrap() - 1.5).abs() < f64::EPSILON);
+        assert!((db.incr_float("float", -0.25).unwrap() - 1.25).abs() < f64::EPSILON);
+
+        // an integer can be incremented as a float
+        db.set("int", &2).unwrap();
+        assert!((db.incr_float("int", 0.5).unwrap() - 2.5).abs() < f64::EPSILON);
+
+        let loaded = Pickle::load_json("test_incr_decr.db", DumpPolicy::Never).unwrap();
+        assert_eq!(loaded.get::<i64>("counter"), Some(-3));
+        assert_eq!(loaded.get::<i64>("other"), Some(-1));
+        assert_eq!(loaded.get::<f64>("float"), Some(1.25));
+        std::fs::remove_file("test_incr_decr.db").unwrap();
+    }
+
+    #[test]
+    fn test_incr_errors() {
+        let mut db = Pickle::new(
+            "test_incr_errors.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("text", &"abc").unwrap();
+        db.set("float", &1.5).unwrap();
+        db.set("max", &i64::MAX).unwrap();
+        db.lcreate("list").unwrap();
+        db.dcreate("dict").unwrap();
+
+        for key in ["text", "float", "list", "dict"] {
+            let err = db.incr(key, 1).unwrap_err();
+            assert!(matches!(err.get_type(), ErrorType::WrongType));
+        }
+        let err = db.incr_float("text", 1.0).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::WrongType));
+
+        let err = db.incr("max", 1).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::Overflow));
+        let err = db.decr("max", i64::MIN).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::Overflow));
+        let err = db.incr_float("float", f64::INFINITY).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::Overflow));
+
+        // failed calls leave the values alone
+        assert_eq!(db.get::<String>("text").unwrap(), "abc");
+        assert_eq!(db.get::<i64>("max"), Some(i64::MAX));
+        assert_eq!(db.get::<f64>("float"), Some(1.5));
+        assert_eq!(db.llen("list"), 0);
+        assert!(db.exists("list"));
+        assert!(db.exists("dict"));
+        drop(db);
+        std::fs::remove_file("test_incr_errors.db").unwrap();
+    }
+
+    #[test]
+    fn test_incr_dumps_and_expiry() {
+        let mut db = Pickle::new(
+            "test_incr_dumps.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.incr("counter", 1).unwrap();
+        db.decr("counter", 1).unwrap();
+        db.incr_float("float", 1.0).unwrap();
+        assert_eq!(db.pending_changes(), 3);
+
+        // the counter keeps its expiry time
+        db.set_with_ttl("session", &10, Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(db.incr("session", 1).unwrap(), 11);
+        assert!(db.ttl("session").is_some());
+
+        // an expired counter starts over
+        db.set_with_ttl("expired", &10, Duration::from_millis(1))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(db.incr("expired", 1).unwrap(), 1);
+        assert!(db.ttl("expired").is_none());
+        drop(db);
+        std::fs::remove_file("test_incr_dumps.db").unwrap();
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_incr_bincode() {
+        let mut db = Pickle::new(
+            "test_incr_bincode.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Bin,
+        );
+        assert_eq!(db.incr("counter", 5).unwrap(), 5);
+        assert_eq!(db.decr("counter", 7).unwrap(), -2);
+        assert_eq!(db.get::<i64>("counter"), Some(-2));
+
+        // values of another size aren't integers, even if their first 8 bytes could be one
+        db.set("text", &"hello").unwrap();
+        db.set("list", &vec![1u64, 2, 3]).unwrap();
+        db.set("small", &1u32).unwrap();
+        for key in ["text", "list", "small"] {
+            let err = db.incr(key, 1).unwrap_err();
+            assert!(matches!(err.get_type(), ErrorType::WrongType));
+        }
+        assert_eq!(db.get::<String>("text").unwrap(), "hello");
+        assert_eq!(db.get::<Vec<u64>>("list").unwrap(), vec![1, 2, 3]);
+        assert_eq!(db.get::<u32>("small"), Some(1));
+    }
+}
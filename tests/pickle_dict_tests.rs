@@ -0,0 +1,146 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+
+    #[test]
+    fn test_dcreate_dadd_dget() {
+        let mut db = Pickle::new(
+            "test_dict.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+
+        db.dcreate("dict1")
+            .unwrap()
+            .dadd("a", &1)
+            .unwrap()
+            .dadd("b", &2)
+            .unwrap();
+
+        assert!(db.exists("dict1"));
+        assert_eq!(db.key_count(), 1);
+        assert_eq!(db.dget::<i32>("dict1", "a").unwrap(), 1);
+        assert!(db.dget::<i32>("dict1", "c").is_none());
+        assert!(db.dget::<String>("dict1", "a").is_none());
+        assert!(db.dexists("dict1", "b"));
+        assert!(!db.dexists("dict1", "c"));
+        assert!(!db.dexists("no_such_dict", "a"));
+
+        let mut keys = db.dkeys("dict1").unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["a", "b"]);
+        let mut vals = db.dvals::<i32>("dict1").unwrap();
+        vals.sort();
+        assert_eq!(vals, vec![1, 2]);
+        assert_eq!(
+            db.dgetall::<i32>("dict1").unwrap(),
+            HashMap::from([(String::from("a"), 1), (String::from("b"), 2)])
+        );
+
+        assert!(matches!(
+            db.dadd("no_such_dict", "a", &1).err().unwrap().get_type(),
+            ErrorType::NotFound
+        ));
+        drop(db);
+        std::fs::remove_file("test_dict.db").unwrap();
+    }
+
+    #[test]
+    fn test_dpop_drem_dmerge() {
+        let mut db = Pickle::new(
+            "test_dict_mutation.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.dcreate("dict1")
+            .unwrap()
+            .dadd("a", &1)
+            .unwrap()
+            .dadd("b", &2)
+            .unwrap();
+        db.dcreate("dict2")
+            .unwrap()
+            .dadd("b", &20)
+            .unwrap()
+            .dadd("c", &30)
+            .unwrap();
+
+        assert_eq!(db.dmerge("dict1", "dict2").unwrap(), 3);
+        assert_eq!(db.dget::<i32>("dict1", "b").unwrap(), 20);
+        assert_eq!(db.dkeys("dict2").unwrap().len(), 2);
+
+        assert_eq!(db.dpop::<i32>("dict1", "a").unwrap(), 1);
+        assert!(!db.dexists("dict1", "a"));
+        assert!(matches!(
+            db.dpop::<i32>("dict1", "a").unwrap_err().get_type(),
+            ErrorType::NotFound
+        ));
+
+        assert!(db.drem("dict2").unwrap());
+        assert!(!db.drem("dict2").unwrap());
+        assert!(db.dmerge("dict1", "dict2").is_err());
+        drop(db);
+        std::fs::remove_file("test_dict_mutation.db").unwrap();
+    }
+
+    #[test]
+    fn test_set_overrides_dict() {
+        let mut db = Pickle::new(
+            "test_dict_override.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.dcreate("key1").unwrap().dadd("a", &1).unwrap();
+        db.set("key1", &100).unwrap();
+
+        assert!(!db.dexists("key1", "a"));
+        assert_eq!(db.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db.key_count(), 1);
+        drop(db);
+        std::fs::remove_file("test_dict_override.db").unwrap();
+    }
+
+    #[test]
+    fn test_dict_load_db() {
+        let mut db = Pickle::new(
+            "test_dict_load.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &100).unwrap();
+        db.lcreate("list1").unwrap().ladd(&1);
+        db.dcreate("dict1")
+            .unwrap()
+            .dadd("a", &String::from("hello"))
+            .unwrap();
+
+        let db2 = Pickle::load_json("test_dict_load.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1]);
+        assert_eq!(db2.dget::<String>("dict1", "a").unwrap(), "hello");
+        std::fs::remove_file("test_dict_load.db").unwrap();
+    }
+
+    #[test]
+    fn test_load_db_without_dicts() {
+        // the file layout used before dicts were supported
+        std::fs::write(
+            "test_dict_legacy.db",
+            r#"[{"key1":"100"},{"list1":["1","2"]}]"#,
+        )
+        .unwrap();
+
+        let db = Pickle::load_json("test_dict_legacy.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert_eq!(db.key_count(), 2);
+        std::fs::remove_file("test_dict_legacy.db").unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use rusty_pickle::{DropPolicy, DumpPolicy};
+
+    use super::*;
+
+    #[test]
+    fn test_pending_changes() {
+        let mut db = Pickle::new(
+            "test_pending_changes.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        assert!(!db.is_dirty());
+
+        db.set("key1", &1).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+        db.lpop::<i32>("list1", 0).unwrap();
+        assert!(db.is_dirty());
+        assert_eq!(db.pending_changes(), 4);
+
+        // failed changes don't count
+        assert!(db.lpop::<i32>("list1", 5).is_err());
+        assert!(!db.remove("no_such_key").unwrap());
+        assert_eq!(db.pending_changes(), 4);
+
+        db.dump().unwrap();
+        assert!(!db.is_dirty());
+        assert_eq!(db.pending_changes(), 0);
+        std::fs::remove_file("test_pending_changes.db").unwrap();
+    }
+
+    #[test]
+    fn test_pending_changes_auto_dump() {
+        let mut db = Pickle::new(
+            "test_pending_changes_auto.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        assert!(!db.is_dirty());
+
+        let mut db2 = Pickle::new(
+            "test_pending_changes_log.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db2.set("key1", &1).unwrap();
+        db2.set("key2", &2).unwrap();
+        assert!(!db2.is_dirty());
+
+        std::fs::remove_file("test_pending_changes_auto.db").unwrap();
+        std::fs::remove_file("test_pending_changes_log.db").unwrap();
+        std::fs::remove_file("test_pending_changes_log.db.log").unwrap();
+    }
+
+    #[test]
+    fn test_dump_on_drop() {
+        let mut db = Pickle::new(
+            "test_dump_on_drop.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        drop(db);
+
+        let mut db2 = Pickle::load_json("test_dump_on_drop.db", DumpPolicy::UponRequest).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 1);
+
+        db2.set_drop_policy(DropPolicy::Discard);
+        db2.set("key2", &2).unwrap();
+        drop(db2);
+        let db3 = Pickle::load_json("test_dump_on_drop.db", DumpPolicy::Never).unwrap();
+        assert!(!db3.exists("key2"));
+        std::fs::remove_file("test_dump_on_drop.db").unwrap();
+    }
+
+    #[test]
+    fn test_dump_on_drop_error_callback() {
+        std::fs::create_dir_all("test_drop_error_dir").unwrap();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let mut db = Pickle::new(
+            "test_drop_error_dir/test.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        let callback_errors = Arc::clone(&errors);
+        db.on_drop_error(move |err| callback_errors.lock().unwrap().push(err.to_string()));
+        db.set("key1", &1).unwrap();
+
+        // the dump on drop fails because the DB directory is gone
+        std::fs::remove_dir_all("test_drop_error_dir").unwrap();
+        drop(db);
+        assert_eq!(errors.lock().unwrap().len(), 1);
+    }
+}
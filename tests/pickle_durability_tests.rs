@@ -0,0 +1,114 @@
+pub use rusty_pickle::Pickle;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use rusty_pickle::{DumpPolicy, Durability};
+
+    use super::*;
+    use crate::common::counting_db;
+
+    fn dir_entries(dir: &str) -> Vec<String> {
+        let mut entries: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn test_interrupted_dump_keeps_previous_db() {
+        std::fs::create_dir_all("test_interrupted_dir").unwrap();
+        // the dump fails when the serializer is told to, like a crash in the middle of a dump
+        let (mut db, _, interrupt) = counting_db("test_interrupted_dir/test.db");
+        db.set_durability(Durability::SyncFileAndDir);
+        db.set("key1", &1).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+        let content = std::fs::read("test_interrupted_dir/test.db").unwrap();
+
+        interrupt.store(true, Ordering::SeqCst);
+        assert!(db.set("key1", &100).is_err());
+        assert!(db.lremlist::<i32>("list1").is_err());
+        assert!(db.dump().is_err());
+        assert_eq!(
+            std::fs::read("test_interrupted_dir/test.db").unwrap(),
+            content
+        );
+        assert_eq!(dir_entries("test_interrupted_dir"), vec!["test.db"]);
+
+        // a temp file left behind by a process that died while writing it doesn't get in the way
+        std::fs::write("test_interrupted_dir/test.db.temp.1.2.3", "{\"map\":{").unwrap();
+        let db2 = Pickle::load_json("test_interrupted_dir/test.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 1);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+
+        interrupt.store(false, Ordering::SeqCst);
+        db.set("key2", &2).unwrap();
+        let db3 = Pickle::load_json("test_interrupted_dir/test.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db3.key_count(), 3);
+
+        // a temp file that was written but couldn't be renamed into place is cleaned up
+        std::fs::create_dir_all("test_interrupted_dir/dir.db").unwrap();
+        let mut db4 = Pickle::new(
+            "test_interrupted_dir/dir.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        assert!(db4.set("key1", &1).is_err());
+        assert_eq!(
+            dir_entries("test_interrupted_dir"),
+            vec!["dir.db", "test.db", "test.db.temp.1.2.3"]
+        );
+        std::fs::remove_dir_all("test_interrupted_dir").unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_dumps() {
+        std::fs::create_dir_all("test_concurrent_dir").unwrap();
+
+        // every dump gets its own temp file, even when they happen in the same instant
+        let threads: Vec<_> = (0..4)
+            .map(|thread| {
+                std::thread::spawn(move || {
+                    let mut db = Pickle::new(
+                        "test_concurrent_dir/test.db",
+                        DumpPolicy::Auto,
+                        rusty_pickle::SerializationMethod::Json,
+                    );
+                    for i in 0..50 {
+                        db.set(&format!("key{}", thread), &i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let db = Pickle::load_json("test_concurrent_dir/test.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db.key_count(), 1);
+        assert_eq!(dir_entries("test_concurrent_dir"), vec!["test.db"]);
+        std::fs::remove_dir_all("test_concurrent_dir").unwrap();
+    }
+
+    #[test]
+    fn test_durable_append_log() {
+        let mut db = Pickle::new(
+            "test_durable_append_log.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set_durability(Durability::SyncFile);
+        db.set("key1", &1).unwrap();
+        db.set("key2", &2).unwrap();
+
+        let db2 = Pickle::load_json("test_durable_append_log.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key2").unwrap(), 2);
+        std::fs::remove_file("test_durable_append_log.db").unwrap();
+        std::fs::remove_file("test_durable_append_log.db.log").unwrap();
+    }
+}
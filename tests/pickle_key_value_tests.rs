@@ -9,7 +9,7 @@ mod tests {
     #[test]
     fn test_set_get() {
         let mut db = Pickle::new(
-            "test_name",
+            "test_set_get.db",
             DumpPolicy::Auto,
             rusty_pickle::SerializationMethod::Json,
         );
@@ -22,12 +22,14 @@ mod tests {
         assert_eq!(db.get::<i32>("num").unwrap(), num);
         // read a string
         assert_eq!(db.get::<String>("a string").unwrap(), "hello");
+        drop(db);
+        std::fs::remove_file("test_set_get.db").unwrap();
     }
 
     #[test]
     fn test_dump_db() {
         let mut db = Pickle::new(
-            "test_name",
+            "test_dump.db",
             DumpPolicy::UponRequest,
             rusty_pickle::SerializationMethod::Json,
         );
@@ -35,25 +37,38 @@ mod tests {
         let string = String::from("hello");
         db.set("num", &num).unwrap();
         db.set("a string", &string).unwrap();
-        assert!(db.dump().is_ok())
+        assert!(db.dump().is_ok());
+        drop(db);
+        std::fs::remove_file("test_dump.db").unwrap();
     }
 
     #[test]
     fn test_load_db() {
-        let mut db = Pickle::load(
-            "test_name",
+        let mut db = Pickle::new(
+            "test_load.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("a string", &String::from("hello")).unwrap();
+        db.dump().unwrap();
+        drop(db);
+
+        let db = Pickle::load(
+            "test_load.db",
             DumpPolicy::Auto,
             rusty_pickle::SerializationMethod::Json,
         )
         .unwrap();
 
         assert_eq!(db.get::<String>("a string").unwrap(), "hello");
+        drop(db);
+        std::fs::remove_file("test_load.db").unwrap();
     }
 
     #[test]
     fn test_get_all_keys() {
         let mut db = Pickle::new(
-            "test.db",
+            "test_get_all_keys.db",
             DumpPolicy::Auto,
             rusty_pickle::SerializationMethod::Json,
         );
@@ -66,12 +81,14 @@ mod tests {
         }
 
         assert_eq!(db.key_count(), key_count);
+        drop(db);
+        std::fs::remove_file("test_get_all_keys.db").unwrap();
     }
 
     #[test]
     fn test_list_keys() {
         let mut db = Pickle::new(
-            "test.db",
+            "test_list_keys.db",
             DumpPolicy::Auto,
             rusty_pickle::SerializationMethod::Json,
         );
@@ -83,7 +100,9 @@ mod tests {
         }
 
         let key_list = db.list_keys().len();
-        assert_eq!(key_list, 10)
+        assert_eq!(key_list, 10);
+        drop(db);
+        std::fs::remove_file("test_list_keys.db").unwrap();
     }
 
     #[test]
@@ -99,8 +118,10 @@ mod tests {
         db.set("num", &num).unwrap();
         db.set("a string", &string).unwrap();
 
-        db.remove("num");
-        assert_eq!(db.exists("num"), false);
+        db.remove("num").unwrap();
+        assert!(!db.exists("num"));
         assert!(db.exists("a string"));
+        drop(db);
+        std::fs::remove_file("test_remove.db").unwrap();
     }
 }
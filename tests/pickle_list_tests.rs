@@ -0,0 +1,228 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+
+    #[test]
+    fn test_lcreate_ladd_lget() {
+        let mut db = Pickle::new(
+            "test_list.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+
+        db.lcreate("list1")
+            .unwrap()
+            .ladd(&100)
+            .ladd(&200)
+            .lextend(&[300, 400]);
+
+        assert!(db.exists("list1"));
+        assert_eq!(db.llen("list1"), 4);
+        assert_eq!(db.lget::<i32>("list1", 0).unwrap(), 100);
+        assert_eq!(db.lget::<i32>("list1", 3).unwrap(), 400);
+        assert!(db.lget::<i32>("list1", 4).is_none());
+        assert!(db.lget::<String>("list1", 0).is_none());
+        assert_eq!(
+            db.lgetall::<i32>("list1").unwrap(),
+            vec![100, 200, 300, 400]
+        );
+        assert!(db.lexists("list1", &300));
+        assert!(!db.lexists("list1", &500));
+
+        assert_eq!(db.llen("no_such_list"), 0);
+        assert!(db.lgetall::<i32>("no_such_list").is_none());
+        assert!(!db.lexists("no_such_list", &100));
+        drop(db);
+        std::fs::remove_file("test_list.db").unwrap();
+    }
+
+    #[test]
+    fn test_lcreate_overrides_value() {
+        let mut db = Pickle::new(
+            "test_list_override.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+
+        db.set("key1", &100).unwrap();
+        db.lcreate("key1").unwrap();
+
+        assert!(db.get::<i32>("key1").is_none());
+        assert_eq!(db.llen("key1"), 0);
+        assert_eq!(db.key_count(), 1);
+        drop(db);
+        std::fs::remove_file("test_list_override.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_keys_includes_lists_and_dicts() {
+        let mut db = Pickle::new(
+            "test_list_keys_all.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Json,
+        );
+
+        db.set("value", &100).unwrap();
+        db.lcreate("list").unwrap();
+        db.dcreate("dict").unwrap();
+        db.lcreate("expired").unwrap();
+        db.expire("expired", Duration::from_millis(10)).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+
+        let mut keys = db.list_keys();
+        keys.sort();
+        assert_eq!(keys, vec!["dict", "list", "value"]);
+        assert_eq!(keys.len(), db.key_count());
+    }
+
+    #[test]
+    fn test_list_load_db() {
+        let mut db = Pickle::new(
+            "test_list_load.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.lcreate("list1")
+            .unwrap()
+            .lextend(&vec![String::from("a"), String::from("b")]);
+
+        let db2 = Pickle::load_json("test_list_load.db", DumpPolicy::Never).unwrap();
+        assert_eq!(
+            db2.lgetall::<String>("list1").unwrap(),
+            vec![String::from("a"), String::from("b")]
+        );
+        std::fs::remove_file("test_list_load.db").unwrap();
+    }
+
+    #[test]
+    fn test_lcreate_rollback_on_dump_failure() {
+        let mut db = Pickle::new(
+            "no_such_dir/test_list.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+
+        assert!(db.lcreate("list1").is_err());
+        assert!(!db.exists("list1"));
+    }
+
+    #[test]
+    fn test_lpop_negative_index() {
+        let mut db = Pickle::new(
+            "test_lpop.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3, 4]);
+
+        assert_eq!(db.lpop::<i32>("list1", -1).unwrap(), 4);
+        assert_eq!(db.lpop::<i32>("list1", 0).unwrap(), 1);
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![2, 3]);
+
+        assert!(matches!(
+            db.lpop::<i32>("list1", -3).unwrap_err().get_type(),
+            ErrorType::OutOfRange
+        ));
+        assert!(matches!(
+            db.lpop::<i32>("no_such_list", 0).unwrap_err().get_type(),
+            ErrorType::NotFound
+        ));
+        // a type mismatch leaves the list untouched
+        assert!(db.lpop::<String>("list1", 0).is_err());
+        assert_eq!(db.llen("list1"), 2);
+        drop(db);
+        std::fs::remove_file("test_lpop.db").unwrap();
+    }
+
+    #[test]
+    fn test_linsert_lset_lremvalue() {
+        let mut db = Pickle::new(
+            "test_linsert.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+
+        db.linsert("list1", 0, &0)
+            .unwrap()
+            .linsert(-1, &10)
+            .unwrap()
+            .lset(1, &100)
+            .unwrap()
+            .linsert(5, &4)
+            .unwrap();
+        assert_eq!(
+            db.lgetall::<i32>("list1").unwrap(),
+            vec![0, 100, 2, 10, 3, 4]
+        );
+
+        assert!(db.lremvalue("list1", &10).unwrap());
+        assert!(!db.lremvalue("list1", &10).unwrap());
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![0, 100, 2, 3, 4]);
+
+        db.lset("list1", -1, &40)
+            .unwrap()
+            .lpop(0)
+            .unwrap()
+            .lremvalue(&2)
+            .unwrap();
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![100, 3, 40]);
+
+        assert!(matches!(
+            db.lset("list1", 3, &0).err().unwrap().get_type(),
+            ErrorType::OutOfRange
+        ));
+        assert!(db.lremvalue("no_such_list", &0).is_err());
+        drop(db);
+        std::fs::remove_file("test_linsert.db").unwrap();
+    }
+
+    #[test]
+    fn test_lremlist() {
+        let mut db = Pickle::new(
+            "test_lremlist.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+
+        assert!(db.lremlist::<String>("list1").is_err());
+        assert!(db.exists("list1"));
+        assert_eq!(db.lremlist::<i32>("list1").unwrap(), vec![1, 2, 3]);
+        assert!(!db.exists("list1"));
+        assert!(matches!(
+            db.lremlist::<i32>("list1").unwrap_err().get_type(),
+            ErrorType::NotFound
+        ));
+        drop(db);
+        std::fs::remove_file("test_lremlist.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_mutation_rollback_on_dump_failure() {
+        std::fs::create_dir_all("test_list_rollback_dir").unwrap();
+        let mut db = Pickle::new(
+            "test_list_rollback_dir/test.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+
+        // every following dump fails because the DB directory is gone
+        std::fs::remove_dir_all("test_list_rollback_dir").unwrap();
+
+        assert!(db.lpop::<i32>("list1", 0).is_err());
+        assert!(db.linsert("list1", 0, &0).is_err());
+        assert!(db.lset("list1", 0, &0).is_err());
+        assert!(db.lremvalue("list1", &2).is_err());
+        assert!(db.lremlist::<i32>("list1").is_err());
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![1, 2, 3]);
+    }
+}
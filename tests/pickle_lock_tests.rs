@@ -0,0 +1,108 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::{DumpPolicy, LockMode};
+
+    use super::*;
+
+    fn load_locked(db_path: &str, lock_mode: LockMode) -> rusty_pickle::error::Result<Pickle> {
+        Pickle::load_locked(
+            db_path,
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+            lock_mode,
+        )
+    }
+
+    #[test]
+    fn test_exclusive_lock() {
+        let mut db = Pickle::new_locked(
+            "test_lock_exclusive.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+            LockMode::TryExclusive,
+        )
+        .unwrap();
+        db.set("key1", &1).unwrap();
+
+        for lock_mode in [LockMode::TryExclusive, LockMode::TryShared] {
+            let err = load_locked("test_lock_exclusive.db", lock_mode)
+                .err()
+                .unwrap();
+            assert!(matches!(err.get_type(), ErrorType::Locked));
+        }
+        // a DB loaded without a lock doesn't look at it
+        assert!(Pickle::load_json("test_lock_exclusive.db", DumpPolicy::Never).is_ok());
+
+        drop(db);
+        let db2 = load_locked("test_lock_exclusive.db", LockMode::TryExclusive).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 1);
+        drop(db2);
+        std::fs::remove_file("test_lock_exclusive.db").unwrap();
+        std::fs::remove_file("test_lock_exclusive.db.lock").unwrap();
+    }
+
+    #[test]
+    fn test_shared_lock() {
+        let mut db = Pickle::new(
+            "test_lock_shared.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+
+        let mut reader1 = load_locked("test_lock_shared.db", LockMode::TryShared).unwrap();
+        let reader2 = load_locked("test_lock_shared.db", LockMode::Shared).unwrap();
+        assert_eq!(reader2.get::<i32>("key1").unwrap(), 1);
+        let err = load_locked("test_lock_shared.db", LockMode::TryExclusive)
+            .err()
+            .unwrap();
+        assert!(matches!(err.get_type(), ErrorType::Locked));
+
+        // readers can't write the DB
+        let err = reader1.set("key1", &2).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::Locked));
+        assert_eq!(reader1.get::<i32>("key1").unwrap(), 1);
+        assert!(matches!(
+            reader1.dump().unwrap_err().get_type(),
+            ErrorType::Locked
+        ));
+
+        drop(reader1);
+        drop(reader2);
+        assert!(load_locked("test_lock_shared.db", LockMode::TryExclusive).is_ok());
+        std::fs::remove_file("test_lock_shared.db").unwrap();
+        std::fs::remove_file("test_lock_shared.db.lock").unwrap();
+    }
+
+    #[test]
+    fn test_exclusive_lock_waits() {
+        let mut db = Pickle::new_locked(
+            "test_lock_wait.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+            LockMode::Exclusive,
+        )
+        .unwrap();
+        db.set("key1", &1).unwrap();
+
+        let start = Instant::now();
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            db.set("key1", &2).unwrap();
+        });
+
+        // the lock is only handed over once the first writer is done with it
+        let db2 = load_locked("test_lock_wait.db", LockMode::Exclusive).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 2);
+        writer.join().unwrap();
+        drop(db2);
+        std::fs::remove_file("test_lock_wait.db").unwrap();
+        std::fs::remove_file("test_lock_wait.db.lock").unwrap();
+    }
+}
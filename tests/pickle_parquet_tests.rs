@@ -0,0 +1,136 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(all(test, feature = "parquet"))]
+mod tests {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+
+    #[test]
+    fn test_export_parquet_rows() {
+        let mut db = Pickle::new(
+            "test_export.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &100).unwrap();
+        db.set("key2", &String::from("hello")).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1.5, 2.5]);
+        db.export_parquet("test_export.parquet").unwrap();
+
+        let file = std::fs::File::open("test_export.parquet").unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                r#"{key: "key1", kind: "value", index: null, value_json: "100", value_bytes: null}"#,
+                r#"{key: "key2", kind: "value", index: null, value_json: ""hello"", value_bytes: null}"#,
+                r#"{key: "list1", kind: "list", index: 0, value_json: "1.5", value_bytes: null}"#,
+                r#"{key: "list1", kind: "list", index: 1, value_json: "2.5", value_bytes: null}"#,
+            ]
+        );
+        std::fs::remove_file("test_export.parquet").unwrap();
+        drop(db);
+        std::fs::remove_file("test_export.db").unwrap();
+    }
+
+    #[test]
+    fn test_import_parquet() {
+        let mut db = Pickle::new(
+            "test_import_src.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &vec![1, 2]).unwrap();
+        db.lcreate("list1")
+            .unwrap()
+            .lextend(&[String::from("a"), String::from("b")]);
+        db.export_parquet("test_import.parquet").unwrap();
+
+        let mut db2 = Pickle::new(
+            "test_import_dst.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db2.set("key2", &true).unwrap();
+        db2.dcreate("list1").unwrap().dadd("a", &1).unwrap();
+        db2.import_parquet("test_import.parquet").unwrap();
+
+        assert_eq!(db2.get::<Vec<i32>>("key1").unwrap(), vec![1, 2]);
+        assert_eq!(db2.lgetall::<String>("list1").unwrap(), vec!["a", "b"]);
+        assert!(!db2.dexists("list1", "a"));
+        assert!(db2.get::<bool>("key2").unwrap());
+        assert_eq!(db2.key_count(), 3);
+
+        let db3 = Pickle::load_json("test_import_dst.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db3.lgetall::<String>("list1").unwrap(), vec!["a", "b"]);
+
+        assert!(matches!(
+            db2.import_parquet("no_such_file.parquet")
+                .unwrap_err()
+                .get_type(),
+            ErrorType::Io
+        ));
+        assert!(matches!(
+            db2.import_parquet("test_import_dst.db")
+                .unwrap_err()
+                .get_type(),
+            ErrorType::Serialization
+        ));
+        std::fs::remove_file("test_import.parquet").unwrap();
+        std::fs::remove_file("test_import_dst.db").unwrap();
+        drop(db);
+        std::fs::remove_file("test_import_src.db").unwrap();
+    }
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_parquet_bincode_round_trip() {
+        let mut db = Pickle::new(
+            "test_parquet_bin_src.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Bin,
+        );
+        db.set("key1", &100).unwrap();
+        db.set("key2", &String::from("hello")).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1.5, 2.5]);
+        db.export_parquet("test_parquet_bin.parquet").unwrap();
+
+        // bincode values can't be converted to json, so they're exported as they are
+        let file = std::fs::File::open("test_parquet_bin.parquet").unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let row = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        assert!(row.to_string().contains("value_json: null"));
+
+        let mut db2 = Pickle::new(
+            "test_parquet_bin_dst.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Bin,
+        );
+        db2.import_parquet("test_parquet_bin.parquet").unwrap();
+        assert_eq!(db2.get::<i32>("key1"), Some(100));
+        assert_eq!(db2.get::<String>("key2").unwrap(), "hello");
+        assert_eq!(db2.lgetall::<f64>("list1").unwrap(), vec![1.5, 2.5]);
+
+        // json values can't be imported into a bincode DB
+        let mut json_db = Pickle::new(
+            "test_parquet_bin_json.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        json_db.set("key3", &1).unwrap();
+        json_db.export_parquet("test_parquet_json.parquet").unwrap();
+        let err = db2.import_parquet("test_parquet_json.parquet").unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::Serialization));
+        assert!(!db2.exists("key3"));
+
+        std::fs::remove_file("test_parquet_bin.parquet").unwrap();
+        std::fs::remove_file("test_parquet_json.parquet").unwrap();
+    }
+}
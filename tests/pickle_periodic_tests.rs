@@ -0,0 +1,79 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rusty_pickle::{DumpPolicy, PeriodicPickle};
+
+    use super::*;
+
+    fn periodic_db(db_path: &str, interval: Duration) -> PeriodicPickle {
+        PeriodicPickle::new(Pickle::new(
+            db_path,
+            DumpPolicy::Periodic(interval),
+            rusty_pickle::SerializationMethod::Json,
+        ))
+    }
+
+    #[test]
+    fn test_periodic_flush_after_burst() {
+        let db = periodic_db("test_periodic_burst.db", Duration::from_millis(100));
+        for i in 0..10 {
+            db.lock().set(&format!("key{}", i), &i).unwrap();
+        }
+        assert!(!std::path::Path::new("test_periodic_burst.db").exists());
+
+        // the last changes of the burst reach the file without any further writes
+        std::thread::sleep(Duration::from_millis(400));
+        let db2 = Pickle::load_json("test_periodic_burst.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.key_count(), 10);
+
+        drop(db);
+        std::fs::remove_file("test_periodic_burst.db").unwrap();
+    }
+
+    #[test]
+    fn test_periodic_flush_on_drop() {
+        let db = periodic_db("test_periodic_drop.db", Duration::from_secs(60));
+        db.lock().set("key1", &1).unwrap();
+        db.lock().lcreate("list1").unwrap().lextend(&[1, 2]);
+        drop(db);
+
+        let db2 = Pickle::load_json("test_periodic_drop.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 1);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+
+        let db3 = periodic_db("test_periodic_drop.db", Duration::from_secs(60));
+        db3.lock().set("key2", &2).unwrap();
+        db3.close().unwrap();
+        let db4 = Pickle::load_json("test_periodic_drop.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db4.key_count(), 1);
+        std::fs::remove_file("test_periodic_drop.db").unwrap();
+    }
+
+    #[test]
+    fn test_periodic_zero_interval() {
+        let db = periodic_db("test_periodic_zero.db", Duration::ZERO);
+        for i in 0..10 {
+            db.lock().set(&format!("key{}", i), &i).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(50));
+        db.close().unwrap();
+
+        let db2 = Pickle::load_json("test_periodic_zero.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.key_count(), 10);
+        std::fs::remove_file("test_periodic_zero.db").unwrap();
+    }
+
+    #[test]
+    fn test_periodic_close_reports_error() {
+        std::fs::create_dir_all("test_periodic_close_dir").unwrap();
+        let db = periodic_db("test_periodic_close_dir/test.db", Duration::from_secs(60));
+        db.lock().set("key1", &1).unwrap();
+
+        // the final dump fails because the DB directory is gone
+        std::fs::remove_dir_all("test_periodic_close_dir").unwrap();
+        assert!(db.close().is_err());
+    }
+}
@@ -0,0 +1,98 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+
+    #[test]
+    fn test_rename() {
+        let mut db = Pickle::new(
+            "test_rename.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &100).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+        db.dcreate("dict1").unwrap().dadd("a", &1).unwrap();
+        db.set_with_ttl("session", &1, Duration::from_secs(60))
+            .unwrap();
+
+        db.rename("key1", "key2", false).unwrap();
+        db.rename("list1", "list2", false).unwrap();
+        db.rename("dict1", "dict2", false).unwrap();
+        db.rename("session", "session2", false).unwrap();
+        assert!(!db.exists("key1") && !db.exists("list1") && !db.exists("dict1"));
+        assert!(db.ttl("session2").is_some());
+        assert!(db.ttl("session").is_none());
+
+        let loaded = Pickle::load_json("test_rename.db", DumpPolicy::Never).unwrap();
+        assert_eq!(loaded.get::<i32>("key2"), Some(100));
+        assert_eq!(loaded.lgetall::<i32>("list2").unwrap(), vec![1, 2, 3]);
+        assert_eq!(loaded.dget::<i32>("dict2", "a"), Some(1));
+        assert!(loaded.ttl("session2").is_some());
+        assert!(!loaded.exists("key1") && !loaded.exists("list1"));
+        std::fs::remove_file("test_rename.db").unwrap();
+    }
+
+    #[test]
+    fn test_rename_overwrite() {
+        let mut db = Pickle::new(
+            "test_rename_overwrite.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+
+        let err = db.rename("key1", "list1", false).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::KeyExists));
+        let err = db.copy("key1", "list1", false).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::KeyExists));
+        let err = db.rename("no_such_key", "key2", false).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::NotFound));
+        assert_eq!(db.llen("list1"), 2);
+
+        // a value can replace a list and the other way around
+        db.rename("key1", "list1", true).unwrap();
+        assert_eq!(db.get::<i32>("list1"), Some(1));
+        assert_eq!(db.llen("list1"), 0);
+        db.lcreate("list2").unwrap().lextend(&[3]);
+        db.copy("list2", "list1", true).unwrap();
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![3]);
+        assert_eq!(db.get::<i32>("list1"), None);
+
+        // renaming a key to itself changes nothing
+        db.rename("list1", "list1", false).unwrap();
+        assert_eq!(db.llen("list1"), 1);
+        drop(db);
+        std::fs::remove_file("test_rename_overwrite.db").unwrap();
+    }
+
+    #[test]
+    fn test_copy() {
+        std::fs::create_dir_all("test_copy_dir").unwrap();
+        let mut db = Pickle::new(
+            "test_copy_dir/test.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+        db.copy("list1", "list2", false).unwrap();
+        db.ladd("list2", &3);
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert_eq!(db.lgetall::<i32>("list2").unwrap(), vec![1, 2, 3]);
+
+        // a failed dump restores both keys
+        std::fs::remove_dir_all("test_copy_dir").unwrap();
+        assert!(db.copy("list1", "list2", true).is_err());
+        assert_eq!(db.lgetall::<i32>("list2").unwrap(), vec![1, 2, 3]);
+        assert!(db.rename("list1", "list2", true).is_err());
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert_eq!(db.lgetall::<i32>("list2").unwrap(), vec![1, 2, 3]);
+    }
+}
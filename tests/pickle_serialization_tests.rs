@@ -0,0 +1,321 @@
+pub use rusty_pickle::Pickle;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::{DumpPolicy, JsonSerializer};
+
+    use super::*;
+    use crate::common::counting_db;
+
+    #[test]
+    fn test_serialization_method_from_i32() {
+        assert!(matches!(
+            rusty_pickle::SerializationMethod::from(0),
+            rusty_pickle::SerializationMethod::Json
+        ));
+        assert!(matches!(
+            rusty_pickle::SerializationMethod::from(5),
+            rusty_pickle::SerializationMethod::PickleDbCompat
+        ));
+        #[cfg(feature = "bincode")]
+        assert!(matches!(
+            rusty_pickle::SerializationMethod::from(1),
+            rusty_pickle::SerializationMethod::Bin
+        ));
+        #[cfg(feature = "yaml")]
+        assert!(matches!(
+            rusty_pickle::SerializationMethod::from(2),
+            rusty_pickle::SerializationMethod::Yaml
+        ));
+        #[cfg(feature = "cbor")]
+        assert!(matches!(
+            rusty_pickle::SerializationMethod::from(3),
+            rusty_pickle::SerializationMethod::Cbor
+        ));
+        #[cfg(feature = "msgpack")]
+        assert!(matches!(
+            rusty_pickle::SerializationMethod::from(4),
+            rusty_pickle::SerializationMethod::MsgPack
+        ));
+    }
+
+    #[cfg(not(feature = "bincode"))]
+    #[test]
+    #[should_panic(expected = "requires the `bincode` feature")]
+    fn test_serialization_method_from_disabled_feature() {
+        let _ = rusty_pickle::SerializationMethod::from(1);
+    }
+
+    #[test]
+    fn test_custom_serializer() {
+        let (mut db, dumps, fail) = counting_db("test_custom_serializer.db");
+
+        db.set("key1", &100).unwrap();
+        db.lcreate("list1").unwrap().ladd(&1);
+        assert_eq!(dumps.load(Ordering::SeqCst), 3);
+
+        fail.store(true, Ordering::SeqCst);
+        let err = db.set("key1", &200).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::Serialization));
+        assert_eq!(db.get::<i32>("key1").unwrap(), 100);
+
+        // the custom serializer writes plain json, so the file can be loaded either way
+        let db2 = Pickle::load_with_serializer(
+            "test_custom_serializer.db",
+            DumpPolicy::Never,
+            Box::new(JsonSerializer::new()),
+        )
+        .unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1]);
+        std::fs::remove_file("test_custom_serializer.db").unwrap();
+    }
+
+    #[test]
+    fn test_json_db_layout() {
+        let mut db = Pickle::new(
+            "test_json_layout.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &100).unwrap();
+        db.set("key2", &String::from("hello")).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+        db.dcreate("dict1").unwrap().dadd("a", &vec![1.5]).unwrap();
+
+        // values are embedded as real json, not as strings holding json
+        let content = std::fs::read_to_string("test_json_layout.db").unwrap();
+        assert_eq!(
+            content,
+            r#"{"map":{"key1":100,"key2":"hello"},"list_map":{"list1":[1,2]},"dict_map":{"dict1":{"a":[1.5]}}}"#
+        );
+
+        let db2 = Pickle::load_json("test_json_layout.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db2.get::<String>("key2").unwrap(), "hello");
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert!(db2.lexists("list1", &2));
+        assert_eq!(db2.dget::<Vec<f64>>("dict1", "a").unwrap(), vec![1.5]);
+        std::fs::remove_file("test_json_layout.db").unwrap();
+    }
+
+    #[test]
+    fn test_json_upgrade_legacy_db() {
+        // the layout used before values were stored as real json
+        std::fs::write(
+            "test_json_legacy.db",
+            r#"[{"key1":"100","key2":"\"hello\""},{"list1":["1","2"]},{"dict1":{"a":"true"}}]"#,
+        )
+        .unwrap();
+
+        let mut db = Pickle::load_json("test_json_legacy.db", DumpPolicy::Auto).unwrap();
+        assert_eq!(db.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db.get::<String>("key2").unwrap(), "hello");
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert!(db.dget::<bool>("dict1", "a").unwrap());
+
+        // the next dump writes the file in the current layout
+        db.set("key3", &3).unwrap();
+        let content = std::fs::read_to_string("test_json_legacy.db").unwrap();
+        assert!(content.starts_with(r#"{"map":{"key1":100,"key2":"hello","key3":3}"#));
+
+        let db2 = Pickle::load_json("test_json_legacy.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<String>("key2").unwrap(), "hello");
+        assert!(db2.dget::<bool>("dict1", "a").unwrap());
+        std::fs::remove_file("test_json_legacy.db").unwrap();
+    }
+
+    #[test]
+    fn test_pickledb_compat_db() {
+        let mut db = Pickle::new(
+            "test_pickledb_compat.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::PickleDbCompat,
+        );
+        db.set("key1", &100).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+        db.dcreate("dict1").unwrap().dadd("a", &1.5).unwrap();
+
+        let content = std::fs::read_to_string("test_pickledb_compat.db").unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&content).unwrap(),
+            serde_json::json!({"key1": 100, "list1": [1, 2], "dict1": {"a": 1.5}})
+        );
+
+        let db2 = Pickle::load(
+            "test_pickledb_compat.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::PickleDbCompat,
+        )
+        .unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert_eq!(db2.dget::<f64>("dict1", "a").unwrap(), 1.5);
+        std::fs::remove_file("test_pickledb_compat.db").unwrap();
+    }
+
+    #[test]
+    fn test_pickledb_compat_load_python_db() {
+        // a file as written by python's pickleDB
+        std::fs::write(
+            "test_pickledb_python.db",
+            r#"{"name": "pickle", "ports": [80, 443], "owner": {"name": "ian", "id": 7}, "empty": null}"#,
+        )
+        .unwrap();
+
+        let db = Pickle::load(
+            "test_pickledb_python.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::PickleDbCompat,
+        )
+        .unwrap();
+        assert_eq!(db.get::<String>("name").unwrap(), "pickle");
+        assert_eq!(db.get::<Option<i32>>("empty").unwrap(), None);
+        assert_eq!(db.lgetall::<u16>("ports").unwrap(), vec![80, 443]);
+        assert_eq!(db.dget::<i32>("owner", "id").unwrap(), 7);
+        assert_eq!(db.dget::<String>("owner", "name").unwrap(), "ian");
+        // arrays are always read back as lists
+        assert!(db.get::<Vec<u16>>("ports").is_none());
+        assert_eq!(db.key_count(), 4);
+        std::fs::remove_file("test_pickledb_python.db").unwrap();
+    }
+
+    #[test]
+    fn test_load_corrupt_db() {
+        std::fs::write("test_corrupt.db", [0xff, 0xfe, 0x00, 0x01]).unwrap();
+
+        let err = Pickle::load_json("test_corrupt.db", DumpPolicy::Never)
+            .err()
+            .unwrap();
+        assert!(matches!(err.get_type(), ErrorType::Serialization));
+        std::fs::remove_file("test_corrupt.db").unwrap();
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bin_load_db() {
+        let mut db = Pickle::new(
+            "test_bin.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Bin,
+        );
+        db.set("key1", &100).unwrap();
+        db.set("key2", &String::from("hello")).unwrap();
+        db.set("key3", &vec![1.5_f64; 1000]).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+        db.dcreate("dict1").unwrap().dadd("a", &1).unwrap();
+
+        let db2 = Pickle::load(
+            "test_bin.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Bin,
+        )
+        .unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db2.get::<String>("key2").unwrap(), "hello");
+        assert_eq!(db2.get::<Vec<f64>>("key3").unwrap(), vec![1.5_f64; 1000]);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2, 3]);
+        assert_eq!(db2.dget::<i32>("dict1", "a").unwrap(), 1);
+
+        // binary files can't be read back as json
+        assert!(Pickle::load_json("test_bin.db", DumpPolicy::Never).is_err());
+        std::fs::remove_file("test_bin.db").unwrap();
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_load_db() {
+        let mut db = Pickle::new(
+            "test_yaml.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Yaml,
+        );
+        db.set("key1", &100).unwrap();
+        db.set("key2", &vec![String::from("a"), String::from("b")])
+            .unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+        db.dcreate("dict1").unwrap().dadd("a", &1.5).unwrap();
+
+        // values are written as nested yaml, not as strings holding yaml
+        let content = std::fs::read_to_string("test_yaml.db").unwrap();
+        assert!(content.contains("key1: 100"));
+        assert!(content.contains("  key2:\n  - a\n  - b"));
+
+        let db2 = Pickle::load(
+            "test_yaml.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Yaml,
+        )
+        .unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db2.get::<Vec<String>>("key2").unwrap(), vec!["a", "b"]);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2, 3]);
+        assert!(db2.lexists("list1", &2));
+        assert_eq!(db2.dget::<f64>("dict1", "a").unwrap(), 1.5);
+        std::fs::remove_file("test_yaml.db").unwrap();
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_load_hand_written_db() {
+        std::fs::write(
+            "test_yaml_hand_written.db",
+            "map:\n  name: pickle\n  point:\n    x: 1\n    y: 2\nlist_map:\n  ports: [80, 443]\n",
+        )
+        .unwrap();
+
+        let db = Pickle::load(
+            "test_yaml_hand_written.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Yaml,
+        )
+        .unwrap();
+        assert_eq!(db.get::<String>("name").unwrap(), "pickle");
+        assert_eq!(
+            db.get::<std::collections::HashMap<String, i32>>("point")
+                .unwrap()["y"],
+            2
+        );
+        assert_eq!(db.lgetall::<u16>("ports").unwrap(), vec![80, 443]);
+        assert_eq!(db.key_count(), 3);
+        std::fs::remove_file("test_yaml_hand_written.db").unwrap();
+    }
+
+    #[cfg(any(feature = "cbor", feature = "msgpack"))]
+    fn check_binary_load_db(db_path: &str, method: fn() -> rusty_pickle::SerializationMethod) {
+        let mut db = Pickle::new(db_path, DumpPolicy::Auto, method());
+        db.set("key1", &100).unwrap();
+        db.set("key2", &String::from("hello")).unwrap();
+        db.set("key3", &vec![0_u8, 1, 255]).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+        db.dcreate("dict1").unwrap().dadd("a", &1.5).unwrap();
+
+        let db2 = Pickle::load(db_path, DumpPolicy::Never, method()).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 100);
+        assert_eq!(db2.get::<String>("key2").unwrap(), "hello");
+        assert_eq!(db2.get::<Vec<u8>>("key3").unwrap(), vec![0, 1, 255]);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2, 3]);
+        assert!(db2.lexists("list1", &3));
+        assert_eq!(db2.dget::<f64>("dict1", "a").unwrap(), 1.5);
+        std::fs::remove_file(db_path).unwrap();
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor_load_db() {
+        check_binary_load_db("test_cbor.db", || rusty_pickle::SerializationMethod::Cbor);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_load_db() {
+        check_binary_load_db("test_msgpack.db", || {
+            rusty_pickle::SerializationMethod::MsgPack
+        });
+    }
+}
@@ -0,0 +1,85 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier};
+
+    use rusty_pickle::{DumpPolicy, SharedPickle};
+
+    use super::*;
+
+    fn shared_db(db_path: &str, dump_policy: DumpPolicy) -> SharedPickle {
+        SharedPickle::new(Pickle::new(
+            db_path,
+            dump_policy,
+            rusty_pickle::SerializationMethod::Json,
+        ))
+    }
+
+    #[test]
+    fn test_shared_writes_from_threads() {
+        let db = shared_db("test_shared_writes.db", DumpPolicy::Auto);
+        let threads: Vec<_> = (0..4)
+            .map(|thread| {
+                let db = db.clone();
+                std::thread::spawn(move || {
+                    for i in 0..10 {
+                        db.set(&format!("key{}_{}", thread, i), &i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(db.key_count(), 40);
+        assert_eq!(db.get::<i32>("key3_9"), Some(9));
+        assert!(db.remove("key3_9").unwrap());
+        assert!(!db.exists("key3_9"));
+
+        // Auto dumps every change, just like a plain Pickle
+        let db2 = Pickle::load_json("test_shared_writes.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.key_count(), 39);
+        std::fs::remove_file("test_shared_writes.db").unwrap();
+    }
+
+    #[test]
+    fn test_shared_parallel_reads() {
+        let db = shared_db("test_shared_reads.db", DumpPolicy::Never);
+        db.set("key1", &1).unwrap();
+
+        // both readers hold the lock at the same time, or the barrier would never open
+        let barrier = Arc::new(Barrier::new(2));
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let db = db.clone();
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    let guard = db.read();
+                    barrier.wait();
+                    guard.get::<i32>("key1")
+                })
+            })
+            .collect();
+        for reader in readers {
+            assert_eq!(reader.join().unwrap(), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_shared_dump_on_last_drop() {
+        let db = shared_db("test_shared_drop.db", DumpPolicy::UponRequest);
+        db.write().lcreate("list1").unwrap().lextend(&[1, 2, 3]);
+        assert_eq!(db.lpop::<i32>("list1", 0).unwrap(), 1);
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![2, 3]);
+
+        let db_clone = db.clone();
+        drop(db);
+        assert!(!std::path::Path::new("test_shared_drop.db").exists());
+
+        drop(db_clone);
+        let db2 = Pickle::load_json("test_shared_drop.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![2, 3]);
+        std::fs::remove_file("test_shared_drop.db").unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rusty_pickle::error::ErrorType;
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+
+    #[test]
+    fn test_append_strlen() {
+        let mut db = Pickle::new(
+            "test_append.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        assert_eq!(db.strlen("key1").unwrap(), 0);
+        assert_eq!(db.append("key1", "héllo").unwrap(), 5);
+        assert_eq!(db.append("key1", ", wörld").unwrap(), 12);
+        assert_eq!(db.strlen("key1").unwrap(), 12);
+
+        let loaded = Pickle::load_json("test_append.db", DumpPolicy::Never).unwrap();
+        assert_eq!(loaded.get::<String>("key1").unwrap(), "héllo, wörld");
+        std::fs::remove_file("test_append.db").unwrap();
+    }
+
+    #[test]
+    fn test_getrange() {
+        let mut db = Pickle::new(
+            "test_getrange.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &"añadir").unwrap();
+        assert_eq!(db.getrange("key1", 0, 2).unwrap(), "aña");
+        assert_eq!(db.getrange("key1", -3, -1).unwrap(), "dir");
+        assert_eq!(db.getrange("key1", 0, -1).unwrap(), "añadir");
+        assert_eq!(db.getrange("key1", -100, 100).unwrap(), "añadir");
+        assert_eq!(db.getrange("key1", 4, 2).unwrap(), "");
+        assert_eq!(db.getrange("key1", 10, 20).unwrap(), "");
+        assert_eq!(db.getrange("no_such_key", 0, -1).unwrap(), "");
+
+        db.set("empty", &"").unwrap();
+        assert_eq!(db.getrange("empty", 0, -1).unwrap(), "");
+    }
+
+    #[test]
+    fn test_string_errors() {
+        let mut db = Pickle::new(
+            "test_string_errors.db",
+            DumpPolicy::UponRequest,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("num", &100).unwrap();
+        db.lcreate("list").unwrap();
+        db.dcreate("dict").unwrap();
+        db.dump().unwrap();
+
+        for key in ["num", "list", "dict"] {
+            let err = db.append(key, "more").unwrap_err();
+            assert!(matches!(err.get_type(), ErrorType::WrongType));
+            let err = db.strlen(key).unwrap_err();
+            assert!(matches!(err.get_type(), ErrorType::WrongType));
+            let err = db.getrange(key, 0, -1).unwrap_err();
+            assert!(matches!(err.get_type(), ErrorType::WrongType));
+        }
+        assert_eq!(db.get::<i32>("num"), Some(100));
+        assert!(!db.is_dirty());
+
+        // an appended string keeps its expiry time
+        db.set_with_ttl("session", &"abc", Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(db.append("session", "def").unwrap(), 6);
+        assert!(db.ttl("session").is_some());
+        drop(db);
+        std::fs::remove_file("test_string_errors.db").unwrap();
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_string_bincode() {
+        let mut db = Pickle::new(
+            "test_string_bincode.db",
+            DumpPolicy::Never,
+            rusty_pickle::SerializationMethod::Bin,
+        );
+        db.set("text", &"abc").unwrap();
+        assert_eq!(db.append("text", "def").unwrap(), 6);
+        assert_eq!(db.strlen("text").unwrap(), 6);
+
+        // a value that starts with a string isn't one
+        db.set("pair", &("abc".to_string(), 7u32)).unwrap();
+        let err = db.append("pair", "def").unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::WrongType));
+        let err = db.strlen("pair").unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::WrongType));
+        let err = db.getrange("pair", 0, -1).unwrap_err();
+        assert!(matches!(err.get_type(), ErrorType::WrongType));
+        assert_eq!(
+            db.get::<(String, u32)>("pair"),
+            Some(("abc".to_string(), 7))
+        );
+    }
+}
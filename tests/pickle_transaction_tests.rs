@@ -0,0 +1,200 @@
+pub use rusty_pickle::Pickle;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use rusty_pickle::error::{Error, ErrorType};
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+    use crate::common::counting_db;
+
+    #[derive(Debug)]
+    enum TransferError {
+        Db,
+        Insufficient,
+    }
+
+    impl From<Error> for TransferError {
+        fn from(_err: Error) -> TransferError {
+            TransferError::Db
+        }
+    }
+
+    #[test]
+    fn test_transaction_commit() {
+        let (mut db, dumps, _) = counting_db("test_transaction_commit.db");
+        db.set("key1", &1).unwrap();
+        assert_eq!(dumps.load(Ordering::SeqCst), 1);
+
+        db.transaction(|tx| {
+            tx.remove("key1")?;
+            tx.set("key2", &2)?;
+            tx.lcreate("list1")?.lextend(&[1, 2, 3]);
+            tx.dcreate("dict1")?.dadd("a", &1)?;
+            tx.transaction(|nested| nested.set("key3", &3))
+        })
+        .unwrap();
+        assert_eq!(dumps.load(Ordering::SeqCst), 2);
+
+        let db2 = Pickle::load_json("test_transaction_commit.db", DumpPolicy::Never).unwrap();
+        assert!(!db2.exists("key1"));
+        assert_eq!(db2.get::<i32>("key3").unwrap(), 3);
+        assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2, 3]);
+        assert_eq!(db2.dget::<i32>("dict1", "a").unwrap(), 1);
+        std::fs::remove_file("test_transaction_commit.db").unwrap();
+    }
+
+    #[test]
+    fn test_transaction_rollback() {
+        let (mut db, dumps, fail) = counting_db("test_transaction_rollback.db");
+        db.set("balance1", &10).unwrap();
+        db.set("balance2", &0).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+
+        let transfer = |db: &mut Pickle, amount: i32| {
+            db.transaction(|tx| {
+                let balance1 = tx.get::<i32>("balance1").unwrap();
+                let balance2 = tx.get::<i32>("balance2").unwrap();
+                tx.set("balance2", &(balance2 + amount))?;
+                tx.lpop::<i32>("list1", 0)?;
+                if balance1 < amount {
+                    return Err(TransferError::Insufficient);
+                }
+                tx.set("balance1", &(balance1 - amount))?;
+                Ok(())
+            })
+        };
+
+        // the closure fails half way through
+        assert!(matches!(
+            transfer(&mut db, 20),
+            Err(TransferError::Insufficient)
+        ));
+        assert_eq!(db.get::<i32>("balance2").unwrap(), 0);
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert_eq!(dumps.load(Ordering::SeqCst), 4);
+
+        // the dump at the end fails
+        fail.store(true, Ordering::SeqCst);
+        assert!(matches!(transfer(&mut db, 5), Err(TransferError::Db)));
+        assert_eq!(db.get::<i32>("balance1").unwrap(), 10);
+        assert_eq!(db.get::<i32>("balance2").unwrap(), 0);
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+
+        // a failed nested transaction only undoes its own changes
+        fail.store(false, Ordering::SeqCst);
+        db.transaction(|tx| {
+            tx.set("key1", &1)?;
+            let nested = tx.transaction(|nested| {
+                nested.set("key2", &2)?;
+                nested.lpop::<i32>("list1", 10)
+            });
+            assert!(matches!(
+                nested.unwrap_err().get_type(),
+                ErrorType::OutOfRange
+            ));
+            Ok::<_, Error>(())
+        })
+        .unwrap();
+        assert!(db.exists("key1"));
+        assert!(!db.exists("key2"));
+        assert_eq!(dumps.load(Ordering::SeqCst), 5);
+        std::fs::remove_file("test_transaction_rollback.db").unwrap();
+    }
+
+    #[test]
+    fn test_transaction_rollback_restores_changed_keys() {
+        let mut db = Pickle::new(
+            "test_transaction_keys.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set_with_ttl("session", &1, Duration::from_secs(60))
+            .unwrap();
+        db.dcreate("dict1").unwrap().dadd("a", &1).unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+
+        let result = db.transaction(|tx| {
+            tx.persist("session")?;
+            tx.rename("dict1", "dict2", false)?;
+            tx.lcreate("list1")?;
+            tx.set("key1", &1)?;
+            // an inner transaction changes a key the outer one changed already
+            tx.transaction(|nested| nested.set("key1", &2))?;
+            tx.remove("list1")?;
+            Err::<(), _>(TransferError::Insufficient)
+        });
+        assert!(matches!(result, Err(TransferError::Insufficient)));
+        assert!(db.ttl("session").is_some());
+        assert_eq!(db.dget::<i32>("dict1", "a"), Some(1));
+        assert!(!db.exists("dict2"));
+        assert_eq!(db.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+        assert!(!db.exists("key1"));
+        std::fs::remove_file("test_transaction_keys.db").unwrap();
+    }
+
+    #[test]
+    fn test_transaction_panic() {
+        let (mut db, dumps, _) = counting_db("test_transaction_panic.db");
+        db.set("key1", &1).unwrap();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            db.transaction(|tx| {
+                tx.set("key2", &2)?;
+                tx.lcreate("list1")?;
+                panic!("the closure panics");
+                #[allow(unreachable_code)]
+                Ok::<_, Error>(())
+            })
+        }));
+        assert!(result.is_err());
+        assert!(!db.exists("key2"));
+        assert!(!db.exists("list1"));
+
+        // the DB is dumped as usual afterwards
+        db.set("key3", &3).unwrap();
+        assert_eq!(dumps.load(Ordering::SeqCst), 2);
+        let db2 = Pickle::load_json("test_transaction_panic.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key3"), Some(3));
+        assert!(!db2.exists("key2"));
+        std::fs::remove_file("test_transaction_panic.db").unwrap();
+    }
+
+    #[test]
+    fn test_transaction_append_log() {
+        let mut db = Pickle::new(
+            "test_transaction_log.db",
+            DumpPolicy::AppendLog,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        let log_len = std::fs::metadata("test_transaction_log.db.log")
+            .unwrap()
+            .len();
+
+        db.transaction(|tx| {
+            tx.set("key1", &100)?;
+            tx.set("key2", &2)?;
+            tx.remove("key2")
+        })
+        .unwrap();
+        assert!(
+            std::fs::metadata("test_transaction_log.db.log")
+                .unwrap()
+                .len()
+                > log_len
+        );
+
+        let db2 = Pickle::load_json("test_transaction_log.db", DumpPolicy::Never).unwrap();
+        assert_eq!(db2.get::<i32>("key1").unwrap(), 100);
+        assert!(!db2.exists("key2"));
+        std::fs::remove_file("test_transaction_log.db").unwrap();
+        std::fs::remove_file("test_transaction_log.db.log").unwrap();
+    }
+}
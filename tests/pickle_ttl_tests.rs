@@ -0,0 +1,150 @@
+pub use rusty_pickle::Pickle;
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use rusty_pickle::DumpPolicy;
+
+    use super::*;
+
+    #[test]
+    fn test_expired_keys_are_invisible() {
+        let mut db = Pickle::new(
+            "test_ttl_invisible.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set("key1", &1).unwrap();
+        db.set_with_ttl("token", &String::from("abc"), Duration::from_millis(100))
+            .unwrap();
+        db.lcreate("list1").unwrap().lextend(&[1, 2]);
+        assert!(db.expire("list1", Duration::from_millis(100)).unwrap());
+        assert_eq!(db.get::<String>("token").unwrap(), "abc");
+        assert!(db.ttl("token").unwrap() <= Duration::from_millis(100));
+        assert_eq!(db.key_count(), 3);
+
+        thread::sleep(Duration::from_millis(150));
+        assert!(db.get::<String>("token").is_none());
+        assert!(!db.exists("token"));
+        assert!(db.ttl("token").is_none());
+        assert_eq!(db.key_count(), 1);
+        assert_eq!(db.list_keys(), vec![String::from("key1")]);
+        assert!(db.lgetall::<i32>("list1").is_none());
+        assert!(db.ladd("list1", &3).is_none());
+
+        // the expired keys are purged from the DB file on the next dump
+        db.dump().unwrap();
+        let content = std::fs::read_to_string("test_ttl_invisible.db").unwrap();
+        assert_eq!(
+            content,
+            "{\"map\":{\"key1\":1},\"list_map\":{},\"dict_map\":{}}"
+        );
+        std::fs::remove_file("test_ttl_invisible.db").unwrap();
+    }
+
+    #[test]
+    fn test_expire_and_persist() {
+        let mut db = Pickle::new(
+            "test_ttl_persist.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        assert!(!db.expire("key1", Duration::from_secs(60)).unwrap());
+        assert!(!db.persist("key1").unwrap());
+
+        db.set("key1", &1).unwrap();
+        assert!(db.ttl("key1").is_none());
+        assert!(db.expire("key1", Duration::from_secs(60)).unwrap());
+        assert!(db.ttl("key1").unwrap() > Duration::from_secs(59));
+        assert!(db.persist("key1").unwrap());
+        assert!(db.ttl("key1").is_none());
+        assert!(!db.persist("key1").unwrap());
+
+        // setting a key again without a TTL makes it permanent
+        db.set_with_ttl("key2", &2, Duration::from_secs(60))
+            .unwrap();
+        db.set("key2", &3).unwrap();
+        assert!(db.ttl("key2").is_none());
+
+        // a key that was removed doesn't keep its TTL
+        db.dcreate("dict1").unwrap().dadd("a", &1).unwrap();
+        db.expire("dict1", Duration::from_millis(50)).unwrap();
+        assert!(db.drem("dict1").unwrap());
+        db.dcreate("dict1").unwrap();
+        thread::sleep(Duration::from_millis(100));
+        assert!(db.exists("dict1"));
+        std::fs::remove_file("test_ttl_persist.db").unwrap();
+    }
+
+    #[test]
+    fn test_ttl_survives_reload() {
+        let mut db = Pickle::new(
+            "test_ttl_reload.db",
+            DumpPolicy::Auto,
+            rusty_pickle::SerializationMethod::Json,
+        );
+        db.set_with_ttl("key1", &1, Duration::from_secs(3600))
+            .unwrap();
+        db.set_with_ttl("key2", &2, Duration::from_millis(100))
+            .unwrap();
+        drop(db);
+
+        let db2 = Pickle::load_json("test_ttl_reload.db", DumpPolicy::Never).unwrap();
+        assert!(db2.ttl("key1").unwrap() > Duration::from_secs(3590));
+        thread::sleep(Duration::from_millis(150));
+        assert!(!db2.exists("key2"));
+
+        // expiry times are replayed from the append log as well
+        let mut db3 = Pickle::load_json("test_ttl_reload.db", DumpPolicy::AppendLog).unwrap();
+        db3.set_with_ttl("key3", &3, Duration::from_secs(3600))
+            .unwrap();
+        db3.persist("key1").unwrap();
+        drop(db3);
+        let db4 = Pickle::load_json("test_ttl_reload.db", DumpPolicy::Never).unwrap();
+        assert!(db4.ttl("key1").is_none());
+        assert!(db4.ttl("key3").unwrap() > Duration::from_secs(3590));
+        assert_eq!(db4.key_count(), 2);
+        std::fs::remove_file("test_ttl_reload.db").unwrap();
+        std::fs::remove_file("test_ttl_reload.db.log").unwrap();
+    }
+
+    #[test]
+    fn test_ttl_in_every_file_format() {
+        // the ids of SerializationMethod::from()
+        #[allow(unused_mut)]
+        let mut methods = vec![0];
+        #[cfg(feature = "bincode")]
+        methods.push(1);
+        #[cfg(feature = "yaml")]
+        methods.push(2);
+        #[cfg(feature = "cbor")]
+        methods.push(3);
+        #[cfg(feature = "msgpack")]
+        methods.push(4);
+
+        for method in methods {
+            let mut db = Pickle::new(
+                "test_ttl_formats.db",
+                DumpPolicy::Auto,
+                rusty_pickle::SerializationMethod::from(method),
+            );
+            db.set("key1", &1).unwrap();
+            db.lcreate("list1").unwrap().lextend(&[1, 2]);
+            db.expire("list1", Duration::from_secs(3600)).unwrap();
+            drop(db);
+
+            let db2 = Pickle::load(
+                "test_ttl_formats.db",
+                DumpPolicy::Never,
+                rusty_pickle::SerializationMethod::from(method),
+            )
+            .unwrap();
+            assert!(db2.ttl("key1").is_none());
+            assert!(db2.ttl("list1").unwrap() > Duration::from_secs(3590));
+            assert_eq!(db2.lgetall::<i32>("list1").unwrap(), vec![1, 2]);
+            std::fs::remove_file("test_ttl_formats.db").unwrap();
+        }
+    }
+}